@@ -0,0 +1,205 @@
+use super::{
+    contractor::Contractor,
+    explorer::Explorer,
+    func::ShipAutomation,
+    hauler::Hauler,
+    loadout::Outfitter,
+    miner,
+    planner::Purchaser,
+    prober::{MarketRefresher, Prober},
+    refinery::Refinery,
+    surveyor::Surveyor,
+    trader::Trader,
+};
+use spacetraders::{
+    enums::{ShipRole, ShipType},
+    responses::schemas::Ship,
+};
+
+use async_trait::async_trait;
+use log::{info, trace, warn};
+use std::{collections::HashMap, fmt::Debug, sync::Arc};
+use tokio::{
+    sync::mpsc,
+    time::{sleep, Duration},
+};
+
+// What a ship does with its time. `run` is one unit of work (a mining trip, a
+// market refresh, ...) - the ship task looks its behavior up again after every
+// call, so swapping a ship to another behavior takes effect between runs.
+#[async_trait]
+pub trait ShipBehavior: Send + Sync + Debug {
+    fn name(&self) -> &'static str;
+    async fn run(&self, ship_automation: &mut ShipAutomation, channel: &mpsc::Sender<Ship>);
+}
+
+// Picks the behavior for every ship - a per ship override wins over the ship
+// type, which wins over the registration role. Anything left over keeps the
+// markets fresh.
+#[derive(Debug)]
+pub struct BehaviorRegistry {
+    behaviors: HashMap<&'static str, Arc<dyn ShipBehavior>>,
+    by_ship: HashMap<String, &'static str>,
+    by_type: HashMap<ShipType, &'static str>,
+    by_role: HashMap<ShipRole, &'static str>,
+}
+impl Default for BehaviorRegistry {
+    fn default() -> Self {
+        let mut registry = BehaviorRegistry {
+            behaviors: HashMap::new(),
+            by_ship: HashMap::new(),
+            by_type: HashMap::new(),
+            by_role: HashMap::new(),
+        };
+        registry.register(Idle);
+        registry.register(MarketRefresher);
+        registry.register(Miner);
+        registry.register(Prober);
+        registry.register(Purchaser);
+        registry.register(Contractor::default());
+        registry.register(Hauler);
+        registry.register(Surveyor);
+        registry.register(Refinery::default());
+        registry.register(Explorer::default());
+        registry.register(Trader::default());
+        registry.register(Outfitter);
+
+        registry.assign_role(ShipRole::Excavator, Miner.name());
+        registry.assign_role(ShipRole::Satellite, Prober.name());
+        registry.assign_role(ShipRole::Command, Contractor::NAME);
+        registry.assign_role(ShipRole::Explorer, Explorer::NAME);
+        registry.assign_role(ShipRole::Hauler, Hauler.name());
+        registry.assign_role(ShipRole::Surveyor, Surveyor.name());
+        registry.assign_role(ShipRole::Refinery, Refinery::NAME);
+        registry
+    }
+}
+impl BehaviorRegistry {
+    pub fn register(&mut self, behavior: impl ShipBehavior + 'static) {
+        trace!("Register Behavior {}", behavior.name());
+        self.behaviors.insert(behavior.name(), Arc::new(behavior));
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn ShipBehavior>> {
+        self.behaviors.get(name).cloned()
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.behaviors.keys().copied().collect()
+    }
+
+    // returns false if no behavior is registered under that name
+    pub fn assign_role(&mut self, role: ShipRole, name: &str) -> bool {
+        match self.behaviors.get_key_value(name) {
+            Some((name, _)) => {
+                self.by_role.insert(role, name);
+                true
+            }
+            None => false,
+        }
+    }
+    pub fn assign_type(&mut self, ship_type: ShipType, name: &str) -> bool {
+        match self.behaviors.get_key_value(name) {
+            Some((name, _)) => {
+                self.by_type.insert(ship_type, name);
+                true
+            }
+            None => false,
+        }
+    }
+    pub fn assign_ship(&mut self, ship_id: &str, name: &str) -> bool {
+        match self.behaviors.get_key_value(name) {
+            Some((name, _)) => {
+                self.by_ship.insert(ship_id.to_string(), name);
+                true
+            }
+            None => false,
+        }
+    }
+    pub fn unassign_ship(&mut self, ship_id: &str) {
+        self.by_ship.remove(ship_id);
+    }
+
+    // parses startup assignments like "SATELLITE=prober", "SHIP_MINING_DRONE=miner"
+    // or "MYAGENT-3=idle" (role, ship type or ship symbol)
+    pub fn configure(&mut self, assignment: &str) -> Result<(), String> {
+        let (key, name) = assignment
+            .split_once('=')
+            .ok_or_else(|| format!("Expected KEY=BEHAVIOR, got {assignment}"))?;
+        if self.get(name).is_none() {
+            return Err(format!(
+                "Unknown behavior {name}, expected one of {:?}",
+                self.names()
+            ));
+        }
+
+        let value = serde_json::Value::String(key.to_string());
+        if let Ok(role) = serde_json::from_value::<ShipRole>(value.clone()) {
+            self.assign_role(role, name);
+        } else {
+            match serde_json::from_value::<ShipType>(value) {
+                Ok(ShipType::Untagged(_)) | Err(_) => self.assign_ship(key, name),
+                Ok(ship_type) => self.assign_type(ship_type, name),
+            };
+        }
+        Ok(())
+    }
+
+    pub fn behavior_for(&self, ship: &Ship) -> Arc<dyn ShipBehavior> {
+        let name = self
+            .by_ship
+            .get(&ship.symbol)
+            .or_else(|| {
+                ship.ship_type()
+                    .and_then(|ship_type| self.by_type.get(&ship_type))
+            })
+            .or_else(|| self.by_role.get(&ship.registration.role))
+            .copied()
+            .unwrap_or(MarketRefresher.name());
+        self.get(name).unwrap_or_else(|| Arc::new(Idle))
+    }
+}
+
+// does nothing, so a ship without a job never burns fuel or credits
+#[derive(Debug)]
+pub struct Idle;
+#[async_trait]
+impl ShipBehavior for Idle {
+    fn name(&self) -> &'static str {
+        "idle"
+    }
+    async fn run(&self, _ship_automation: &mut ShipAutomation, _channel: &mpsc::Sender<Ship>) {
+        sleep(Duration::from_secs(60)).await;
+    }
+}
+
+#[derive(Debug)]
+pub struct Miner;
+#[async_trait]
+impl ShipBehavior for Miner {
+    fn name(&self) -> &'static str {
+        "miner"
+    }
+    async fn run(&self, ship_automation: &mut ShipAutomation, _channel: &mpsc::Sender<Ship>) {
+        miner::mine(ship_automation, false).await;
+    }
+}
+
+impl ShipAutomation {
+    // swaps the ship to another behavior once its current run finishes
+    pub async fn set_behavior(&self, name: &str) -> bool {
+        trace!("Set Behavior");
+        let assigned = self
+            .write()
+            .await
+            .automation_data
+            .behaviors
+            .assign_ship(&self.ship_id, name);
+        if assigned {
+            info!("{} is now running {}", self.ship_id, name);
+        } else {
+            warn!("{} can't run unknown behavior {}", self.ship_id, name);
+        }
+        assigned
+    }
+}
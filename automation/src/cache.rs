@@ -8,11 +8,12 @@ use spacetraders::{
 use async_recursion::async_recursion;
 use chrono::{DateTime, Local};
 use indextree::{Arena, NodeId};
-use log::{info, trace};
-use serde::{Deserialize, Serialize};
+use log::{error, info, trace, warn};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{
-    fs::{remove_file, File},
-    path::Path,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter},
+    path::{Path, PathBuf},
     sync::Arc,
 };
 use tokio::{
@@ -20,23 +21,195 @@ use tokio::{
     task::{self, JoinHandle},
 };
 
-const DISTANCESDB_FILE: &str = "distances.cbor";
-const GATESDB_FILE: &str = "gates.cbor";
+// bump this whenever the layout of a cached dataset changes
+const CACHE_VERSION: u32 = 1;
+const MANIFEST_FILE: &str = "manifest.cbor";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dataset {
+    Systems,
+    Distances,
+    Waypoints,
+    Gates,
+    Markets,
+    Shipyards,
+}
+impl Dataset {
+    pub const ALL: [Dataset; 6] = [
+        Dataset::Systems,
+        Dataset::Distances,
+        Dataset::Waypoints,
+        Dataset::Gates,
+        Dataset::Markets,
+        Dataset::Shipyards,
+    ];
+
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            Dataset::Systems => "systems.cbor",
+            Dataset::Distances => "distances.cbor",
+            Dataset::Waypoints => "waypoints.cbor",
+            Dataset::Gates => "gates.cbor",
+            Dataset::Markets => "markets.cbor",
+            Dataset::Shipyards => "shipyards.cbor",
+        }
+    }
+}
 
 #[derive(Debug, Deserialize, Serialize)]
 struct CachedData<T> {
+    version: u32,
+    reset_date: DateTime<Local>,
     date: DateTime<Local>,
     data: T,
 }
-pub fn cache_data<T: Serialize>(data: T, file_name: &str) {
-    ciborium::into_writer(
-        &CachedData {
-            date: Local::now(),
-            data,
-        },
-        &File::create(file_name).unwrap(),
-    )
-    .unwrap();
+
+// Persistent storage for everything we learn about the universe.
+// Every dataset is tagged with the reset it was downloaded in, so a
+// server reset (or a new CACHE_VERSION) throws all of it away.
+#[derive(Debug, Clone)]
+pub struct Cache {
+    data_dir: PathBuf,
+    reset_date: DateTime<Local>,
+}
+impl Cache {
+    pub async fn new(space_traders: &SpaceTraders, data_dir: impl Into<PathBuf>) -> Self {
+        let reset_date = space_traders
+            .get_status()
+            .await
+            .expect("Failed to get server status")
+            .reset_date;
+        Cache::open(data_dir, reset_date)
+    }
+
+    pub fn open(data_dir: impl Into<PathBuf>, reset_date: DateTime<Local>) -> Self {
+        let cache = Cache {
+            data_dir: data_dir.into(),
+            reset_date,
+        };
+        fs::create_dir_all(&cache.data_dir).expect("Failed to create data directory");
+
+        match cache.read_file::<()>(&cache.path(MANIFEST_FILE)) {
+            Some(_) => info!("Cache in {} is current", cache.data_dir.display()),
+            None => {
+                info!(
+                    "Server reset or cache version changed - invalidating {}",
+                    cache.data_dir.display()
+                );
+                cache.invalidate_all();
+                cache.write_file(&cache.path(MANIFEST_FILE), &());
+            }
+        }
+        cache
+    }
+
+    pub fn data_dir(&self) -> &Path {
+        &self.data_dir
+    }
+    pub fn reset_date(&self) -> DateTime<Local> {
+        self.reset_date
+    }
+    pub fn path(&self, file_name: &str) -> PathBuf {
+        self.data_dir.join(file_name)
+    }
+
+    // returns true if the server has reset since this cache was opened
+    pub async fn check_reset(&mut self, space_traders: &SpaceTraders) -> bool {
+        trace!("Check Reset");
+        match space_traders.get_status().await {
+            Ok(status) if status.reset_date != self.reset_date => {
+                *self = Cache::open(self.data_dir.clone(), status.reset_date);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn load<T: DeserializeOwned>(&self, dataset: Dataset) -> Option<T> {
+        trace!("Load {:?}", dataset);
+        self.read_file(&self.path(dataset.file_name()))
+    }
+
+    pub fn store<T: Serialize>(&self, dataset: Dataset, data: &T) {
+        trace!("Store {:?}", dataset);
+        self.write_file(&self.path(dataset.file_name()), data)
+    }
+
+    pub fn invalidate(&self, dataset: Dataset) {
+        trace!("Invalidate {:?}", dataset);
+        let path = self.path(dataset.file_name());
+        if path.is_file() {
+            if let Err(err) = fs::remove_file(&path) {
+                error!("Failed to remove {}: {}", path.display(), err);
+            }
+        }
+    }
+
+    pub fn invalidate_all(&self) {
+        for dataset in Dataset::ALL {
+            self.invalidate(dataset);
+        }
+    }
+
+    fn read_file<T: DeserializeOwned>(&self, path: &Path) -> Option<T> {
+        let file = File::open(path).ok()?;
+        match ciborium::from_reader::<CachedData<T>, _>(BufReader::new(file)) {
+            Err(_) => {
+                warn!("{} is corrupted", path.display());
+                None
+            }
+            Ok(cached) if cached.version != CACHE_VERSION => {
+                info!("{} is from an older cache version", path.display());
+                None
+            }
+            Ok(cached) if cached.reset_date != self.reset_date => {
+                info!("{} is outdated", path.display());
+                None
+            }
+            Ok(cached) => Some(cached.data),
+        }
+    }
+
+    // writes to a temporary file first so a crash never leaves a half written dataset
+    fn write_file<T: Serialize>(&self, path: &Path, data: &T) {
+        let tmp_path = path.with_extension("tmp");
+        let result = File::create(&tmp_path)
+            .and_then(|file| {
+                let mut writer = BufWriter::new(file);
+                ciborium::into_writer(
+                    &CachedData {
+                        version: CACHE_VERSION,
+                        reset_date: self.reset_date,
+                        date: Local::now(),
+                        data,
+                    },
+                    &mut writer,
+                )
+                .map_err(|err| io::Error::other(err.to_string()))?;
+                writer.into_inner()?.sync_all()
+            })
+            .and_then(|_| fs::rename(&tmp_path, path));
+
+        if let Err(err) = result {
+            error!("Failed to write {}: {}", path.display(), err);
+            let _ = fs::remove_file(&tmp_path);
+        }
+    }
+}
+
+pub async fn get_systems(space_traders: &SpaceTraders, cache: &Cache) -> Vec<schemas::System> {
+    trace!("Get Systems");
+    if let Some(systems) = cache.load(Dataset::Systems) {
+        return systems;
+    }
+
+    info!("Downloading all systems");
+    let systems = space_traders
+        .list_all_systems()
+        .await
+        .expect("Failed to download systems.json");
+    cache.store(Dataset::Systems, &systems);
+    systems
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -47,108 +220,59 @@ pub struct AllEuclideanDistances {
     pub euclidean_distance: Vec<EuclideanDistances>,
 }
 
-#[async_recursion]
-pub async fn build_euclidean_distance(space_traders: &SpaceTraders) -> Vec<AllEuclideanDistances> {
+pub async fn build_euclidean_distance(
+    space_traders: &SpaceTraders,
+    cache: &Cache,
+) -> Vec<AllEuclideanDistances> {
     trace!("Building Euclidean Distances");
 
-    if Path::new(DISTANCESDB_FILE).is_file() {
-        let distance_file: Result<
-            CachedData<Vec<AllEuclideanDistances>>,
-            ciborium::de::Error<std::io::Error>,
-        > = ciborium::from_reader(&File::open(DISTANCESDB_FILE).unwrap());
-        let distances: CachedData<Vec<AllEuclideanDistances>> = match distance_file {
-            Err(_) => {
-                info!("removing currupted {}", DISTANCESDB_FILE);
+    if let Some(distances) = cache.load(Dataset::Distances) {
+        return distances;
+    }
 
-                remove_file(DISTANCESDB_FILE).unwrap();
-                return build_euclidean_distance(space_traders).await;
-            }
-            Ok(data) => {
-                info!("{} integrity check good", DISTANCESDB_FILE);
+    let systems: Arc<Mutex<Vec<schemas::System>>> =
+        Arc::new(Mutex::new(get_systems(space_traders, cache).await));
 
-                // if data.date < space_traders.get_status().await.unwrap().reset_date {
-                //     info!("{} is outdated", DISTANCESDB_FILE);
-                //     remove_file(DISTANCESDB_FILE).unwrap();
-                //     return build_euclidean_distance(space_traders).await;
-                // }
+    info!("Calclulating System Distances");
 
-                data
-            }
-        };
+    let all_euclidean_distance: Arc<Mutex<Vec<AllEuclideanDistances>>> =
+        Arc::new(Mutex::new(Vec::new()));
+    let mut euclidean_handles: Vec<JoinHandle<()>> = Vec::new();
 
-        distances.data
-    } else {
-        let num_systems = space_traders.get_status().await.unwrap().stats.systems;
-
-        info!(
-            "{} does not exist - Downloading {} systems",
-            DISTANCESDB_FILE, num_systems,
-        );
-
-        let systems: Arc<Mutex<Vec<schemas::System>>> = Arc::new(Mutex::new(Vec::new()));
-        // let mut systems_handles: Vec<JoinHandle<()>> = Vec::new();
+    let systems_len = systems.lock().await.len();
+    let loop_systems = systems.lock().await.clone();
 
-        // for task_numers in 1..(((num_systems / 20) + 1) / 100) {
-        let new_space_traders = SpaceTraders::new_random().await;
+    for system in loop_systems {
+        let all_euclidean_distance = all_euclidean_distance.clone();
         let systems = systems.clone();
-        // systems_handles.push(task::spawn(async move {
-        // for _ in 1..task_numers {
-        for system in new_space_traders
-            .list_systems(false)
-            .await
-            .unwrap()
-            .data
-            .iter()
-        {
-            systems.lock().await.push(system.clone());
-        }
-        // }
-        // }));
-        // }
-
-        // for handle in systems_handles.into_iter() {
-        // handle.await.unwrap();
-        // }
-
-        info!("Calclulating System Distances");
-
-        let all_euclidean_distance: Arc<Mutex<Vec<AllEuclideanDistances>>> =
-            Arc::new(Mutex::new(Vec::new()));
-        let mut euclidean_handles: Vec<JoinHandle<()>> = Vec::new();
-
-        let systems_len = systems.lock().await.len();
-        let loop_systems = systems.lock().await.clone();
-
-        for system in loop_systems {
-            let all_euclidean_distance = all_euclidean_distance.clone();
-            let systems = systems.clone();
-            euclidean_handles.push(task::spawn(async move {
-                let distances = AllEuclideanDistances {
-                    name: system.symbol.system.clone(),
-                    x: system.x,
-                    y: system.y,
-                    euclidean_distance: euclidean_distance(
-                        &system,
-                        systems,
-                        Some(systems_len.try_into().unwrap()),
-                    )
-                    .await,
-                };
-                all_euclidean_distance.lock().await.push(distances)
-            }));
-        }
-
-        for handle in euclidean_handles.into_iter() {
-            handle.await.unwrap();
-        }
-        let all_euclidean_distance = all_euclidean_distance.lock().await;
+        euclidean_handles.push(task::spawn(async move {
+            let distances = AllEuclideanDistances {
+                name: system.symbol.system.clone(),
+                x: system.x,
+                y: system.y,
+                euclidean_distance: euclidean_distance(
+                    &system,
+                    systems,
+                    Some(systems_len.try_into().unwrap()),
+                )
+                .await,
+            };
+            all_euclidean_distance.lock().await.push(distances)
+        }));
+    }
 
-        info!("Writing new distances to {}", DISTANCESDB_FILE);
+    for handle in euclidean_handles.into_iter() {
+        handle.await.unwrap();
+    }
+    let all_euclidean_distance = all_euclidean_distance.lock().await;
 
-        cache_data(all_euclidean_distance.clone(), DISTANCESDB_FILE);
+    info!(
+        "Writing new distances to {}",
+        Dataset::Distances.file_name()
+    );
+    cache.store(Dataset::Distances, &*all_euclidean_distance);
 
-        all_euclidean_distance.to_vec()
-    }
+    all_euclidean_distance.to_vec()
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -213,9 +337,16 @@ async fn euclidean_distance(
 
 pub async fn get_gate_network(
     space_traders: &SpaceTraders,
+    cache: &Cache,
     symbol: WaypointString,
 ) -> Option<Arena<schemas::JumpGate>> {
     trace!("Get Gate Network");
+    // the network is only reused when it was walked from the same gate
+    if let Some((root, arena)) = cache.load::<(WaypointString, _)>(Dataset::Gates) {
+        if root == symbol {
+            return Some(arena);
+        }
+    }
     let mut arena = Arena::new();
 
     let root_gate = space_traders.jump_gate(&symbol).await.ok()?;
@@ -227,11 +358,14 @@ pub async fn get_gate_network(
             .data
             .iter()
         {
-            recurse_gate_network(space_traders, &mut arena, &waypoint, None).await;
+            recurse_gate_network(space_traders, &mut arena, waypoint, None).await;
         }
     }
-    info!("Finished getting all gates - writing to {}", GATESDB_FILE);
-    // cache_data(arena, GATESDB_FILE); // TODO: get this to write to a file
+    info!(
+        "Finished getting all gates - writing to {}",
+        Dataset::Gates.file_name()
+    );
+    cache.store(Dataset::Gates, &(&symbol, &arena));
 
     Some(arena)
 }
@@ -264,7 +398,7 @@ async fn recurse_gate_network(
                 .await
                 .unwrap();
             for waypoint in waypoints.data.iter() {
-                recurse_gate_network(space_traders, arena, &waypoint, Some(new_parent)).await;
+                recurse_gate_network(space_traders, arena, waypoint, Some(new_parent)).await;
             }
         }
         info!("{:?}", arena.count()); // TODO: remove this
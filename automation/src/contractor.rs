@@ -1,32 +1,561 @@
-// pub async fn get_contracts(
-//     ship_handler_data: Arc<Mutex<ShipHandlerData>>,
-// ) -> Vec<contracts::schemas::Contract> {
-//     let available_contracts = ship_handler_data
-//         .spacetraders
-//         .list_contracts()
-//         .await
-//         .data;
-
-//     let mut accepted_contracts: Vec<contracts::schemas::Contract> = vec![];
-//     for contract in available_contracts.iter() {
-//         if contract.accepted {
-//             accepted_contracts.push(contract.clone())
-//         }
-//     }
-//     if accepted_contracts.is_empty() {
-//         // TODO // should except the contract that is expiring first!
-//         // or that gives the most rep points
-//         // for now just excepting the first contract for simplicity
-//         accepted_contracts.push(
-//             ship_handler_data
-//                 .spacetraders
-//                 .accept_contract(&available_contracts[0].id)
-//                 .await
-//                 .data
-//                 .contract,
-//         )
-//     } else if accepted_contracts.is_empty() && available_contracts.is_empty() {
-//         warn!("accepted contracts is zero, but there are no contracts to accept");
-//     }
-//     accepted_contracts
-// }
+use super::{
+    behavior::ShipBehavior,
+    func::{travel_seconds, ShipAutomation, FUEL_PER_MARKET_UNIT},
+    hauler,
+    ledger::LedgerEntry,
+    miner, planner,
+};
+use spacetraders::{
+    enums,
+    responses::schemas::{self, Contract, ContractDeliverGood, Ship},
+    SystemString,
+};
+
+use async_trait::async_trait;
+use chrono::Local;
+use log::{error, info, trace, warn};
+use std::{sync::Mutex, time::Instant};
+use tokio::{
+    sync::mpsc,
+    time::{sleep, Duration},
+};
+
+// accepting more contracts than we can work on only loses reputation
+const MAX_ACTIVE_CONTRACTS: usize = 1;
+// contracts we don't expect to finish in this share of the time left are too risky
+const DEADLINE_MARGIN: f64 = 0.75;
+// rough time for a miner to extract one unit, cooldowns included
+pub(crate) const MINING_SECONDS_PER_UNIT: f64 = 10.0;
+// fields yield a random mix of ores, only this share of it is the one a contract wants
+const MINING_YIELD_SHARE: f64 = 0.2;
+// how often the command ship looks for a ship worth buying between contracts
+const PURCHASE_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+// Works one contract at a time: accepts the best offer before its deadline to
+// accept, buys or mines the goods, delivers them and fulfills the contract.
+// Negotiates a new contract whenever there is nothing left on offer. Every so
+// often it grows the fleet too, the command ship is the one that used to.
+#[derive(Debug, Default)]
+pub struct Contractor {
+    last_purchase: Mutex<Option<Instant>>,
+}
+impl Contractor {
+    pub const NAME: &'static str = "contractor";
+
+    fn purchase_due(&self) -> bool {
+        let mut last_purchase = self.last_purchase.lock().unwrap();
+        if last_purchase.is_some_and(|last| last.elapsed() < PURCHASE_INTERVAL) {
+            return false;
+        }
+        *last_purchase = Some(Instant::now());
+        true
+    }
+}
+#[async_trait]
+impl ShipBehavior for Contractor {
+    fn name(&self) -> &'static str {
+        Contractor::NAME
+    }
+    async fn run(&self, ship_automation: &mut ShipAutomation, channel: &mpsc::Sender<Ship>) {
+        if self.purchase_due() {
+            planner::expand_fleet(ship_automation, channel).await;
+        }
+        let Some(contracts) = sync_contracts(ship_automation).await else {
+            sleep(Duration::from_secs(60)).await;
+            return;
+        };
+
+        match next_step(&contracts) {
+            Step::Fulfill(contract) => fulfill_contract(ship_automation, contract).await,
+            Step::Work(contract) => work_on(ship_automation, contract).await,
+            Step::Accept => {
+                let ship = ship_automation.clone_ship().await.unwrap();
+                let offers = rank_offers(ship_automation, &ship, &contracts).await;
+                let Some(offer) = offers.first() else {
+                    // nothing on offer pays, wait for the market to change
+                    sleep(Duration::from_secs(60)).await;
+                    return;
+                };
+                info!(
+                    "{} expects {:.0} credits from contract {} in {} minutes",
+                    ship_automation.ship_id,
+                    offer.profit(),
+                    offer.contract_id,
+                    offer.duration.num_minutes()
+                );
+                let contract = contracts
+                    .iter()
+                    .find(|contract| contract.id == offer.contract_id)
+                    .unwrap();
+                accept_contract(ship_automation, contract).await;
+            }
+            Step::Negotiate => negotiate_contract(ship_automation).await,
+        }
+    }
+}
+
+// What the contractor does next with the contracts it has.
+#[derive(Debug)]
+pub(crate) enum Step<'a> {
+    Fulfill(&'a Contract),
+    Work(&'a Contract),
+    // accept the best of the offers, if one pays
+    Accept,
+    Negotiate,
+}
+
+pub(crate) fn next_step(contracts: &[Contract]) -> Step<'_> {
+    match contracts.iter().find(|contract| is_active(contract)) {
+        Some(contract) if contract.is_complete() => Step::Fulfill(contract),
+        Some(contract) => Step::Work(contract),
+        None if contracts.iter().any(is_offered) => Step::Accept,
+        None => Step::Negotiate,
+    }
+}
+
+// accepted, not yet fulfilled and still in time
+fn is_active(contract: &Contract) -> bool {
+    contract.accepted
+        && !contract.fulfilled
+        && contract.time_to_deadline() > chrono::Duration::zero()
+}
+
+// not accepted yet and there is still time to
+fn is_offered(contract: &Contract) -> bool {
+    !contract.accepted && !contract.fulfilled && contract.deadline_to_accept > Local::now()
+}
+
+// What a contract is expected to earn and how long it would keep the ship busy.
+#[derive(Debug, Clone)]
+pub struct ContractEvaluation {
+    pub contract_id: String,
+    pub payment: f64,
+    // goods bought and fuel burnt
+    pub cost: f64,
+    pub duration: chrono::Duration,
+    // why the contract isn't worth taking, if it isn't
+    pub rejected: Option<String>,
+}
+impl ContractEvaluation {
+    pub fn profit(&self) -> f64 {
+        self.payment - self.cost
+    }
+    pub fn profit_per_hour(&self) -> f64 {
+        let hours = self.duration.num_seconds().max(60) as f64 / 3600.0;
+        self.profit() / hours
+    }
+    fn reject(mut self, reason: impl Into<String>) -> Self {
+        self.rejected = Some(reason.into());
+        self
+    }
+}
+
+// the offers worth accepting, best profit per hour first
+pub async fn rank_offers(
+    ship_automation: &ShipAutomation,
+    ship: &Ship,
+    contracts: &[Contract],
+) -> Vec<ContractEvaluation> {
+    trace!("Rank Offers");
+    // every contract that runs out unfulfilled costs us reputation
+    let active = contracts
+        .iter()
+        .filter(|contract| is_active(contract))
+        .count();
+    if active >= MAX_ACTIVE_CONTRACTS {
+        return Vec::new();
+    }
+
+    let mut offers = Vec::new();
+    for contract in contracts.iter().filter(|contract| is_offered(contract)) {
+        let evaluation = evaluate_contract(ship_automation, ship, contract).await;
+        match &evaluation.rejected {
+            Some(reason) => info!(
+                "{} rejects contract {}: {}",
+                ship_automation.ship_id, contract.id, reason
+            ),
+            None => offers.push(evaluation),
+        }
+    }
+    offers.sort_by(|a, b| b.profit_per_hour().total_cmp(&a.profit_per_hour()));
+    offers
+}
+
+// Estimates profit and time for the ship to do the contract on its own: buy
+// each good at the cheapest known market (or mine it), and haul it to the
+// destination in as many trips as the hold needs.
+pub async fn evaluate_contract(
+    ship_automation: &ShipAutomation,
+    ship: &Ship,
+    contract: &Contract,
+) -> ContractEvaluation {
+    trace!("Evaluate Contract");
+    let payment = &contract.terms.payment;
+    let evaluation = ContractEvaluation {
+        contract_id: contract.id.clone(),
+        payment: (payment.on_accepted + payment.on_fulfilled) as f64,
+        cost: 0.0,
+        duration: chrono::Duration::zero(),
+        rejected: None,
+    };
+    let now = Local::now();
+    if !contract.accepted && contract.deadline_to_accept <= now {
+        return evaluation.reject("it can no longer be accepted");
+    }
+    if contract.r#type != enums::ListContractsType::Procurement {
+        return evaluation.reject(format!("{:?} contracts aren't supported", contract.r#type));
+    }
+
+    let system = &ship.nav.system_symbol;
+    let capacity = ship.cargo.capacity.max(1);
+    let fuel_price = ship_automation
+        .read()
+        .await
+        .automation_data
+        .store
+        .best_purchase_price(system, &enums::TradeSymbol::Fuel)
+        .map_or(0.0, |price| price.purchase_price / FUEL_PER_MARKET_UNIT);
+    let fleet_has_laser = ship_automation
+        .clone_ships()
+        .await
+        .values()
+        .flat_map(|ship| ship.mounts.iter())
+        .any(|mount| mount.symbol.extractor() == Some(enums::Extractor::MiningLaser));
+
+    let mut cost = 0.0;
+    let mut seconds = 0.0;
+    for good in contract.terms.deliver.iter() {
+        let remaining = good.remaining_units() as i32;
+        if remaining == 0 {
+            continue;
+        }
+        let Some(symbol) = &good.trade_symbol else {
+            return evaluation.reject("it asks for an unknown good");
+        };
+        let Some(destination) = hauler::waypoint_string(&good.destination_symbol) else {
+            return evaluation.reject("its destination is invalid");
+        };
+        if destination.to_system() != *system {
+            return evaluation.reject("its destination is outside the system");
+        }
+        let destination = ship_automation.get_waypoint(&destination).await;
+
+        let best_price = ship_automation
+            .read()
+            .await
+            .automation_data
+            .store
+            .best_purchase_price(system, symbol);
+        let (source, unit_cost, gather_seconds) = match best_price {
+            Some(price) => {
+                let market = ship_automation.get_waypoint(&price.waypoint).await;
+                ((market.x, market.y), price.purchase_price, 0.0)
+            }
+            None if fleet_has_laser
+                && symbol.extractor() == Some(enums::Extractor::MiningLaser) =>
+            {
+                let Some(field) = nearest_field(ship_automation, system, &destination).await else {
+                    return evaluation.reject(format!("nowhere to mine {:?}", symbol));
+                };
+                let gather_seconds =
+                    remaining as f64 * MINING_SECONDS_PER_UNIT / MINING_YIELD_SHARE;
+                (field, 0.0, gather_seconds)
+            }
+            None => return evaluation.reject(format!("nowhere to get {:?}", symbol)),
+        };
+
+        let trips = ((remaining + capacity - 1) / capacity) as f64;
+        let distance = ship_automation
+            .euclidean_distance(source.0, source.1, destination.x, destination.y)
+            .max(1) as f64;
+        cost += remaining as f64 * unit_cost + 2.0 * trips * distance * fuel_price;
+        seconds += gather_seconds + 2.0 * trips * travel_seconds(distance, ship.engine.speed);
+    }
+
+    let evaluation = ContractEvaluation {
+        cost,
+        duration: chrono::Duration::seconds(seconds as i64),
+        ..evaluation
+    };
+    let time_left = contract.time_to_deadline().num_seconds() as f64;
+    if seconds > time_left * DEADLINE_MARGIN {
+        evaluation.reject("it would likely miss the deadline")
+    } else if evaluation.profit() <= 0.0 {
+        evaluation.reject("it doesn't pay")
+    } else {
+        evaluation
+    }
+}
+
+// the asteroid or debris field closest to the waypoint
+async fn nearest_field(
+    ship_automation: &ShipAutomation,
+    system: &SystemString,
+    waypoint: &schemas::Waypoint,
+) -> Option<(i32, i32)> {
+    ship_automation
+        .read()
+        .await
+        .automation_data
+        .store
+        .waypoints_in_system(system)
+        .into_iter()
+        .filter(|field| {
+            field.r#type == enums::WaypointType::AsteroidField
+                || field.r#type == enums::WaypointType::DebrisField
+        })
+        .min_by_key(|field| {
+            ship_automation.euclidean_distance(field.x, field.y, waypoint.x, waypoint.y)
+        })
+        .map(|field| (field.x, field.y))
+}
+
+// refreshes our copy of every contract, None if the server can't be asked
+async fn sync_contracts(ship_automation: &ShipAutomation) -> Option<Vec<Contract>> {
+    trace!("Sync Contracts");
+    let contracts = ship_automation
+        .read()
+        .await
+        .st_interface
+        .list_contracts(false)
+        .await;
+    let contracts = match contracts {
+        Ok(contracts) => contracts.data,
+        Err(err) => {
+            error!(
+                "{} Failed to get Contracts: {:?}",
+                ship_automation.ship_id, err
+            );
+            return None;
+        }
+    };
+    for contract in contracts.iter() {
+        ship_automation
+            .add_contract(&contract.id, contract.clone())
+            .await;
+    }
+    Some(contracts)
+}
+
+async fn accept_contract(ship_automation: &ShipAutomation, contract: &Contract) {
+    trace!("Accept Contract");
+    let response = ship_automation
+        .read()
+        .await
+        .st_interface
+        .accept_contract(&contract.id)
+        .await;
+    match response {
+        Ok(response) => {
+            let response = response.data;
+            info!(
+                "{} accepted contract {} for {} credits up front",
+                ship_automation.ship_id, contract.id, contract.terms.payment.on_accepted
+            );
+            ship_automation
+                .add_contract(&contract.id, response.contract)
+                .await;
+            ship_automation
+                .record_transaction(LedgerEntry::contract(
+                    &ship_automation.ship_id,
+                    &contract.id,
+                    contract.terms.payment.on_accepted as f64,
+                    response.agent.credits,
+                ))
+                .await;
+        }
+        Err(err) => error!(
+            "{} failed to accept contract {}: {:?}",
+            ship_automation.ship_id, contract.id, err
+        ),
+    }
+}
+
+async fn negotiate_contract(ship_automation: &ShipAutomation) {
+    trace!("Negotiate Contract");
+    if !ship_automation.ensure_docked().await {
+        return;
+    }
+    let response = ship_automation
+        .read()
+        .await
+        .st_interface
+        .negotiate_contract(&ship_automation.ship_id)
+        .await;
+    match response {
+        Ok(response) => {
+            let contract = response.data.contract;
+            info!(
+                "{} negotiated contract {}",
+                ship_automation.ship_id, contract.id
+            );
+            ship_automation
+                .add_contract(&contract.id, contract.clone())
+                .await;
+        }
+        Err(err) => {
+            // most likely not at a faction's waypoint, try again later
+            warn!(
+                "{} failed to negotiate a contract: {:?}",
+                ship_automation.ship_id, err
+            );
+            sleep(Duration::from_secs(60)).await;
+        }
+    }
+}
+
+async fn fulfill_contract(ship_automation: &ShipAutomation, contract: &Contract) {
+    trace!("Fulfill Contract");
+    let response = ship_automation
+        .read()
+        .await
+        .st_interface
+        .fulfill_contract(&contract.id)
+        .await;
+    match response {
+        Ok(response) => {
+            let response = response.data;
+            info!(
+                "{} fulfilled contract {} for {} credits",
+                ship_automation.ship_id, contract.id, contract.terms.payment.on_fulfilled
+            );
+            ship_automation
+                .add_contract(&contract.id, response.contract)
+                .await;
+            ship_automation
+                .record_transaction(LedgerEntry::contract(
+                    &ship_automation.ship_id,
+                    &contract.id,
+                    contract.terms.payment.on_fulfilled as f64,
+                    response.agent.credits,
+                ))
+                .await;
+        }
+        Err(err) => {
+            error!(
+                "{} failed to fulfill contract {}: {:?}",
+                ship_automation.ship_id, contract.id, err
+            );
+            sleep(Duration::from_secs(60)).await;
+        }
+    }
+}
+
+// delivers what we hold, otherwise goes and gets more of the first good missing
+async fn work_on(ship_automation: &mut ShipAutomation, contract: &Contract) {
+    trace!("Work On Contract");
+    let ship = ship_automation.clone_ship().await.unwrap();
+    for task in plan_work(&ship, contract) {
+        match task {
+            Task::Deliver(good, units) => {
+                deliver(ship_automation, contract, good, units).await;
+                return;
+            }
+            Task::Procure(symbol, units) => {
+                info!(
+                    "{} contract {} needs {} more {:?}, due {}",
+                    ship_automation.ship_id, contract.id, units, symbol, contract.terms.deadline
+                );
+                if procure(ship_automation, &ship, contract, symbol, units).await {
+                    return;
+                }
+            }
+            // make room, or leave the goods to the miners and haulers for now
+            Task::Unload => hauler::unload(ship_automation).await,
+            Task::Wait => sleep(Duration::from_secs(60)).await,
+        }
+    }
+}
+
+// One thing to do for a contract, the ones after it are only tried if it fails.
+#[derive(Debug)]
+pub(crate) enum Task<'a> {
+    Deliver(&'a ContractDeliverGood, i32),
+    Procure(&'a enums::TradeSymbol, i32),
+    Unload,
+    Wait,
+}
+
+// delivers a good once we hold all that's missing (or as much as fits), else
+// tries to get more of every good missing in turn
+pub(crate) fn plan_work<'a>(ship: &Ship, contract: &'a Contract) -> Vec<Task<'a>> {
+    let free = ship.cargo.capacity - ship.cargo.units;
+    let mut tasks = Vec::new();
+    for good in contract.terms.deliver.iter() {
+        let remaining = good.remaining_units() as i32;
+        let Some(symbol) = &good.trade_symbol else {
+            continue;
+        };
+        if remaining == 0 {
+            continue;
+        }
+
+        let held = hauler::held_units(ship, symbol);
+        if held >= remaining || (held > 0 && free == 0) {
+            return vec![Task::Deliver(good, held.min(remaining))];
+        }
+        if free == 0 {
+            break;
+        }
+        tasks.push(Task::Procure(symbol, (remaining - held).min(free)));
+    }
+    tasks.push(if free == 0 { Task::Unload } else { Task::Wait });
+    tasks
+}
+
+async fn deliver(
+    ship_automation: &ShipAutomation,
+    contract: &Contract,
+    good: &ContractDeliverGood,
+    units: i32,
+) {
+    trace!("Deliver");
+    let (Some(symbol), Some(destination)) = (
+        &good.trade_symbol,
+        hauler::waypoint_string(&good.destination_symbol),
+    ) else {
+        warn!("Invalid contract destination {}", good.destination_symbol);
+        return;
+    };
+    if ship_automation.ensure_docked_at(&destination).await {
+        ship_automation
+            .deliver_contract(&contract.id, symbol, units)
+            .await;
+    }
+}
+
+// buys the good if that is cheaper than the contract pays, mines it otherwise
+async fn procure(
+    ship_automation: &mut ShipAutomation,
+    ship: &Ship,
+    contract: &Contract,
+    symbol: &enums::TradeSymbol,
+    units: i32,
+) -> bool {
+    trace!("Procure");
+    let best_price = ship_automation
+        .read()
+        .await
+        .automation_data
+        .store
+        .best_purchase_price(&ship.nav.system_symbol, symbol);
+    if let Some(best_price) = best_price {
+        let cost = best_price.purchase_price * units as f64;
+        let worth_it = cost < contract.terms.payment.on_fulfilled as f64;
+        if worth_it
+            && cost <= ship_automation.get_credits().await
+            && ship_automation.ensure_docked_at(&best_price.waypoint).await
+        {
+            return ship_automation
+                .purchase_cargo_in_chunks(symbol, units, best_price.trade_volume)
+                .await
+                > 0;
+        }
+    }
+
+    if miner::can_mine(ship) {
+        miner::mine(ship_automation, true).await;
+        return true;
+    }
+    false
+}
@@ -1,12 +1,327 @@
+use super::{behavior::ShipBehavior, func::ShipAutomation, store::WaypointRecord};
+use spacetraders::{
+    enums, requests,
+    responses::schemas::{self, Ship},
+    SystemString, WaypointString,
+};
 
+use async_trait::async_trait;
+use log::{error, info, trace, warn};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+};
+use tokio::{
+    sync::mpsc,
+    time::{sleep, Duration},
+};
 
+// Tours the uncharted waypoints of its system nearest first and charts them,
+// scanning around whenever its sensors are off cooldown. Once the system is
+// charted it jumps on to the closest connected system with work left.
+#[derive(Debug, Default)]
+pub struct Explorer {
+    // where every explorer is headed, so two never chart the same waypoint
+    claims: Mutex<HashMap<String, WaypointString>>,
+    // waypoints that could not be charted, so we don't keep coming back
+    skipped: Mutex<HashSet<WaypointString>>,
+    // scans each ship has made, to take turns between the kinds of scan
+    scans: Mutex<HashMap<String, usize>>,
+}
+impl Explorer {
+    pub const NAME: &'static str = "explorer";
+}
+#[async_trait]
+impl ShipBehavior for Explorer {
+    fn name(&self) -> &'static str {
+        Explorer::NAME
+    }
+    async fn run(&self, ship_automation: &mut ShipAutomation, _channel: &mpsc::Sender<Ship>) {
+        ship_automation.wait_for_arrival().await;
+        let ship = ship_automation.clone_ship().await.unwrap();
+        if has_sensors(&ship) && ship_automation.cooldown().await.is_none() {
+            self.scan(ship_automation).await;
+        }
+        ship_automation.chart_waypoint().await;
 
+        let tour = self.plan_tour(ship_automation, &ship).await;
+        if let Some(next) = tour.first() {
+            info!(
+                "{} is exploring {}, {} uncharted waypoints left in {}",
+                ship_automation.ship_id,
+                next.waypoint,
+                tour.len(),
+                ship.nav.system_symbol.system
+            );
+            self.claims
+                .lock()
+                .unwrap()
+                .insert(ship_automation.ship_id.clone(), next.clone());
+            if ship_automation.ensure_orbit_at(next).await {
+                ship_automation.chart_waypoint().await;
+                if !is_charted(ship_automation, next).await {
+                    warn!("{} could not be charted, skipping it", next.waypoint);
+                    self.skipped.lock().unwrap().insert(next.clone());
+                }
+            }
+            self.claims.lock().unwrap().remove(&ship_automation.ship_id);
+            return;
+        }
 
+        if !self.jump_onward(ship_automation, &ship).await {
+            sleep(Duration::from_secs(60)).await;
+        }
+    }
+}
+impl Explorer {
+    // the uncharted waypoints of the system, each one the closest to the last
+    async fn plan_tour(
+        &self,
+        ship_automation: &ShipAutomation,
+        ship: &Ship,
+    ) -> Vec<WaypointString> {
+        trace!("Plan Tour");
+        let system = &ship.nav.system_symbol;
+        let mut waypoints = waypoints_in_system(ship_automation, system).await;
+        if waypoints.is_empty() {
+            ship_automation.get_waypoints(system).await;
+            waypoints = waypoints_in_system(ship_automation, system).await;
+        }
 
+        let claimed: HashSet<WaypointString> = self
+            .claims
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(ship_id, _)| **ship_id != ship_automation.ship_id)
+            .map(|(_, waypoint)| waypoint.clone())
+            .collect();
+        let skipped = self.skipped.lock().unwrap().clone();
+        let mut left: Vec<WaypointRecord> = waypoints
+            .into_iter()
+            .filter(is_uncharted)
+            .filter(|waypoint| !claimed.contains(&waypoint.symbol))
+            .filter(|waypoint| !skipped.contains(&waypoint.symbol))
+            .collect();
 
+        let here = ship_automation
+            .get_waypoint(&ship.nav.waypoint_symbol)
+            .await;
+        let (mut x, mut y) = (here.x, here.y);
+        let mut tour = Vec::new();
+        while !left.is_empty() {
+            let closest = (0..left.len())
+                .min_by_key(|&i| ship_automation.euclidean_distance(x, y, left[i].x, left[i].y))
+                .unwrap();
+            let next = left.swap_remove(closest);
+            (x, y) = (next.x, next.y);
+            tour.push(next.symbol);
+        }
+        tour
+    }
 
+    // takes turns scanning waypoints, systems and ships
+    async fn scan(&self, ship_automation: &ShipAutomation) {
+        trace!("Scan");
+        let turn = {
+            let mut scans = self.scans.lock().unwrap();
+            let turn = scans.entry(ship_automation.ship_id.clone()).or_default();
+            *turn += 1;
+            *turn - 1
+        };
+        let ship_id = &ship_automation.ship_id;
 
+        match turn % 3 {
+            0 => {
+                let response = ship_automation
+                    .read()
+                    .await
+                    .st_interface
+                    .scan_waypoints(ship_id)
+                    .await;
+                match response {
+                    Ok(response) => {
+                        ship_automation
+                            .update_cooldown(&response.data.cooldown)
+                            .await;
+                        let waypoints: Vec<schemas::Waypoint> = response
+                            .data
+                            .waypoints
+                            .into_iter()
+                            .map(Into::into)
+                            .collect();
+                        info!("{} scanned {} waypoints", ship_id, waypoints.len());
+                        ship_automation.record_waypoints(&waypoints).await;
+                    }
+                    Err(err) => error!("{} failed to scan waypoints: {:?}", ship_id, err),
+                }
+            }
+            1 => {
+                let response = ship_automation
+                    .read()
+                    .await
+                    .st_interface
+                    .scan_systems(ship_id)
+                    .await;
+                match response {
+                    Ok(response) => {
+                        ship_automation
+                            .update_cooldown(&response.data.cooldown)
+                            .await;
+                        info!(
+                            "{} scanned {} systems",
+                            ship_id,
+                            response.data.systems.len()
+                        );
+                        ship_automation
+                            .read()
+                            .await
+                            .automation_data
+                            .store
+                            .record_scanned_systems(&response.data.systems);
+                    }
+                    Err(err) => error!("{} failed to scan systems: {:?}", ship_id, err),
+                }
+            }
+            _ => {
+                let response = ship_automation
+                    .read()
+                    .await
+                    .st_interface
+                    .scan_ships(ship_id)
+                    .await;
+                match response {
+                    Ok(response) => {
+                        ship_automation
+                            .update_cooldown(&response.data.cooldown)
+                            .await;
+                        for ship in response.data.ships.iter() {
+                            info!(
+                                "{} sees {} ({:?}) at {}",
+                                ship_id,
+                                ship.symbol,
+                                ship.registration.role,
+                                ship.nav.waypoint_symbol.waypoint
+                            );
+                        }
+                    }
+                    Err(err) => error!("{} failed to scan ships: {:?}", ship_id, err),
+                }
+            }
+        }
+    }
 
-// pub async fn explore(ship_handler_data: Arc<Mutex<ShipHandlerData>>) {
+    // jumps to the closest connected system that still has something to chart
+    async fn jump_onward(&self, ship_automation: &ShipAutomation, ship: &Ship) -> bool {
+        trace!("Jump Onward");
+        let Some(gate) = waypoints_in_system(ship_automation, &ship.nav.system_symbol)
+            .await
+            .into_iter()
+            .find(|waypoint| {
+                waypoint.r#type == enums::WaypointType::JumpGate && !waypoint.under_construction
+            })
+        else {
+            return false;
+        };
 
-// }
+        let mut record = ship_automation
+            .read()
+            .await
+            .automation_data
+            .store
+            .jump_gate(&gate.symbol);
+        if record.is_none() {
+            let unlocked = ship_automation.read().await;
+            if let Ok(jump_gate) = unlocked.st_interface.jump_gate(&gate.symbol).await {
+                unlocked
+                    .automation_data
+                    .store
+                    .record_jump_gate(&gate.symbol, &jump_gate.data);
+                record = unlocked.automation_data.store.jump_gate(&gate.symbol);
+            }
+        }
+        let Some(record) = record else {
+            return false;
+        };
+
+        let mut target = None;
+        for (system, distance) in record.connections.iter() {
+            let waypoints = waypoints_in_system(ship_automation, system).await;
+            let unexplored = waypoints.is_empty() || waypoints.iter().any(is_uncharted);
+            if unexplored && target.as_ref().is_none_or(|(_, best)| distance < best) {
+                target = Some((system.clone(), *distance));
+            }
+        }
+        let Some((target, _)) = target else {
+            return false;
+        };
+
+        if !ship_automation.ensure_orbit_at(&gate.symbol).await {
+            return false;
+        }
+        ship_automation.wait_for_cooldown().await;
+        let response = ship_automation
+            .read()
+            .await
+            .st_interface
+            .jump_ship(
+                &ship_automation.ship_id,
+                requests::JumpShip {
+                    system_symbol: target.system.clone(),
+                },
+            )
+            .await;
+        match response {
+            Ok(response) => {
+                info!(
+                    "{} jumped to {} to explore it",
+                    ship_automation.ship_id, target.system
+                );
+                ship_automation.update_nav(response.data.nav).await;
+                ship_automation
+                    .update_cooldown(&response.data.cooldown)
+                    .await;
+                true
+            }
+            Err(err) => {
+                error!(
+                    "{} failed to jump to {}: {:?}",
+                    ship_automation.ship_id, target.system, err
+                );
+                false
+            }
+        }
+    }
+}
+
+pub fn has_sensors(ship: &Ship) -> bool {
+    ship.mounts
+        .iter()
+        .any(|mount| mount.symbol.is_sensor_array())
+}
+
+fn is_uncharted(waypoint: &WaypointRecord) -> bool {
+    !waypoint.charted || waypoint.traits.contains(&enums::WaypointTrait::Uncharted)
+}
+
+async fn is_charted(ship_automation: &ShipAutomation, waypoint: &WaypointString) -> bool {
+    ship_automation
+        .read()
+        .await
+        .automation_data
+        .store
+        .waypoint(waypoint)
+        .is_some_and(|waypoint| !is_uncharted(&waypoint))
+}
+
+async fn waypoints_in_system(
+    ship_automation: &ShipAutomation,
+    system: &SystemString,
+) -> Vec<WaypointRecord> {
+    ship_automation
+        .read()
+        .await
+        .automation_data
+        .store
+        .waypoints_in_system(system)
+}
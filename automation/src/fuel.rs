@@ -0,0 +1,207 @@
+use super::{
+    func::{ShipAutomation, FUEL_PER_MARKET_UNIT},
+    ledger::{Activity, LedgerEntry},
+    store::PriceObservation,
+};
+use spacetraders::{
+    enums::{self, TradeSymbol},
+    requests,
+    responses::schemas::Ship,
+    SpacetradersError, WaypointString,
+};
+
+use log::{error, info, trace, warn};
+
+// fuel kept on top of what the route needs, as a share of the tank
+const FUEL_MARGIN_SHARE: f64 = 0.1;
+// but never less than this
+const MIN_FUEL_MARGIN: u32 = 10;
+// fuel up to this much dearer than the system's cheapest is cheap, and fills the tank
+const CHEAP_FUEL_SHARE: f64 = 1.1;
+
+// fuel burnt cruising the distance
+pub fn fuel_for(distance: u64) -> u32 {
+    distance.max(1) as u32
+}
+
+// fuel to fly to the destination and on to the closest station from there,
+// plus a margin, never more than the tank holds
+pub async fn route_need(
+    ship_automation: &ShipAutomation,
+    ship: &Ship,
+    destination: &WaypointString,
+) -> u32 {
+    trace!("Route Fuel Need");
+    let here = ship_automation
+        .get_waypoint(&ship.nav.waypoint_symbol)
+        .await;
+    let there = ship_automation.get_waypoint(destination).await;
+    let hop = fuel_for(ship_automation.euclidean_distance(here.x, here.y, there.x, there.y));
+
+    // every station in the system we saw fuel prices at
+    let stations: Vec<WaypointString> = ship_automation
+        .read()
+        .await
+        .automation_data
+        .store
+        .latest_prices(&TradeSymbol::Fuel)
+        .into_iter()
+        .map(|price| price.waypoint)
+        .filter(|waypoint| waypoint.to_system() == ship.nav.system_symbol)
+        .collect();
+    let mut onward = Vec::new();
+    for station in stations {
+        if station == *destination {
+            onward.push(0);
+            continue;
+        }
+        let station = ship_automation.get_waypoint(&station).await;
+        onward.push(fuel_for(
+            ship_automation.euclidean_distance(there.x, there.y, station.x, station.y),
+        ));
+    }
+    need_for(ship.fuel.capacity, hop, onward)
+}
+
+// the hop, the cheapest onward leg and the margin, capped at the tank
+pub fn need_for(capacity: u32, hop: u32, onward: impl IntoIterator<Item = u32>) -> u32 {
+    let onward = onward.into_iter().min().unwrap_or(0);
+    let margin = ((capacity as f64 * FUEL_MARGIN_SHARE) as u32).max(MIN_FUEL_MARGIN);
+    (hop + onward + margin).min(capacity)
+}
+
+// Tops the tank up before flying to the destination. Only what the route needs
+// is bought at dear stations, cheap ones fill the tank. Returns false if the
+// ship still can't cruise there.
+pub async fn prepare(ship_automation: &ShipAutomation, destination: &WaypointString) -> bool {
+    trace!("Prepare Fuel");
+    let ship = ship_automation.clone_ship().await.unwrap();
+    // probes have no tank and fly for free
+    if ship.fuel.capacity == 0 {
+        return true;
+    }
+    let need = route_need(ship_automation, &ship, destination).await;
+
+    let mut prices = fuel_prices(ship_automation, &ship).await;
+    // a market we never priced, so look before deciding
+    if prices.0.is_none() {
+        let here = ship_automation
+            .get_waypoint(&ship.nav.waypoint_symbol)
+            .await;
+        if here
+            .traits
+            .iter()
+            .any(|r#trait| r#trait.symbol == enums::WaypointTrait::Marketplace)
+            && ship_automation.get_market(&here).await.is_some()
+        {
+            prices = fuel_prices(ship_automation, &ship).await;
+        }
+    }
+    let target = match prices {
+        (None, _) => return ship.fuel.current >= need,
+        (Some(here), Some(cheapest))
+            if here.purchase_price <= cheapest.purchase_price * CHEAP_FUEL_SHARE =>
+        {
+            ship.fuel.capacity
+        }
+        (Some(_), _) => need,
+    };
+    if ship.fuel.current >= target {
+        return ship.fuel.current >= need;
+    }
+
+    // the market sells whole units, so round up to what one fills
+    let missing = target - ship.fuel.current;
+    let per_unit = FUEL_PER_MARKET_UNIT as u32;
+    let units = (missing.div_ceil(per_unit) * per_unit).min(ship.fuel.capacity - ship.fuel.current);
+    if !refuel(ship_automation, units).await {
+        return ship.fuel.current >= need;
+    }
+
+    let current = ship_automation.clone_ship().await.unwrap().fuel.current;
+    current >= need
+}
+
+// what fuel costs here and at the system's cheapest station
+async fn fuel_prices(
+    ship_automation: &ShipAutomation,
+    ship: &Ship,
+) -> (Option<PriceObservation>, Option<PriceObservation>) {
+    let unlocked = ship_automation.read().await;
+    let store = &unlocked.automation_data.store;
+    (
+        store.latest_price(&ship.nav.waypoint_symbol, &TradeSymbol::Fuel),
+        store.best_purchase_price(&ship.nav.system_symbol, &TradeSymbol::Fuel),
+    )
+}
+
+// buys the fuel at the ship's waypoint, docking first if the server wants it
+pub async fn refuel(ship_automation: &ShipAutomation, units: u32) -> bool {
+    trace!("Refuel");
+    if !ship_automation.ensure_docked().await {
+        return false;
+    }
+    for _ in 0..2 {
+        let response = ship_automation
+            .read()
+            .await
+            .st_interface
+            .refuel_ship(
+                &ship_automation.ship_id,
+                Ok(requests::RefuelShip {
+                    units: units as i32,
+                }),
+            )
+            .await;
+        match response {
+            Ok(response) => {
+                let response = response.data;
+                info!(
+                    "{} refueled {} for {}",
+                    ship_automation.ship_id, units, response.transaction.total_price
+                );
+                ship_automation.update_fuel(response.fuel).await;
+                ship_automation
+                    .record_transaction(LedgerEntry::market(
+                        Activity::Refuel,
+                        &response.transaction,
+                        response.agent.credits,
+                    ))
+                    .await;
+                return true;
+            }
+            // our nav was stale, dock for real and try again
+            Err(SpacetradersError::ShipRefuelDockedError) => {
+                ship_automation.refresh_nav().await;
+                if !ship_automation.ensure_docked().await {
+                    return false;
+                }
+            }
+            // so no ship should count on it again
+            Err(SpacetradersError::ShipRefuelInvalidWaypointError) => {
+                let waypoint = ship_automation
+                    .clone_ship()
+                    .await
+                    .unwrap()
+                    .nav
+                    .waypoint_symbol;
+                warn!(
+                    "{} can't refuel at {}, the market sells no fuel",
+                    ship_automation.ship_id, waypoint.waypoint
+                );
+                ship_automation
+                    .read()
+                    .await
+                    .automation_data
+                    .store
+                    .forget_good(&waypoint, &TradeSymbol::Fuel);
+                return false;
+            }
+            Err(err) => {
+                error!("{} failed to refuel: {:?}", ship_automation.ship_id, err);
+                return false;
+            }
+        }
+    }
+    false
+}
@@ -1,16 +1,18 @@
-use super::Automation;
+use super::{
+    cache::Dataset,
+    fuel,
+    ledger::{Activity, LedgerEntry},
+    Automation,
+};
 use spacetraders::{
-    enums, requests, responses::schemas, SpaceTraders, SystemString, WaypointString,
+    enums, requests, responses::schemas, SpaceTraders, SpacetradersError, SystemString,
+    WaypointString,
 };
 
 // use async_recursion::async_recursion;
-use chrono::{DateTime, Local};
 use log::{error, info, trace};
 use std::{collections::HashMap, sync::Arc};
-use tokio::{
-    sync::{RwLock, RwLockReadGuard, RwLockWriteGuard},
-    time::{sleep, Duration},
-};
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 #[derive(Debug)]
 pub struct SharedAutomationData {
@@ -31,6 +33,8 @@ pub struct ShipAutomation {
     pub ship_id: String,
     pub credits_generated: f64,
     pub symbols_charted: f32,
+    // how often each good was kept because the market paid below the floor
+    pub held_back: HashMap<enums::TradeSymbol, u32>,
 }
 impl ShipAutomation {
     pub fn new(shared_data: Arc<RwLock<SharedAutomationData>>, ship_id: &str) -> Self {
@@ -39,6 +43,7 @@ impl ShipAutomation {
             ship_id: ship_id.to_string(),
             credits_generated: 0.0,
             symbols_charted: 0.0,
+            held_back: HashMap::new(),
         }
     }
 
@@ -53,13 +58,8 @@ impl ShipAutomation {
         trace!("Get Credits");
         self.read().await.automation_data.credits
     }
-    pub async fn add_credits(&self, credits: f64) {
-        trace!("Add Credits");
-        self.write().await.automation_data.credits += credits;
-    }
-    pub async fn sub_credits(self, credits: f64) {
-        trace!("Sub Credits");
-        self.write().await.automation_data.credits -= credits;
+    pub async fn record_transaction(&self, entry: LedgerEntry) {
+        self.write().await.automation_data.record_transaction(entry);
     }
 
     pub async fn clone_ship(&self) -> Option<schemas::Ship> {
@@ -118,115 +118,52 @@ impl ShipAutomation {
         contract: schemas::Contract,
     ) -> Option<schemas::Contract> {
         trace!("Add Contract");
-        self.write()
-            .await
+        let mut unlocked = self.write().await;
+        // whatever the contract still needs is kept from being sold
+        unlocked
+            .automation_data
+            .reservations
+            .reserve_contract(&contract);
+        unlocked
             .automation_data
             .contracts
             .insert(contract_id.to_string(), contract)
     }
 
-    pub async fn remove_survey(&self, waypoint: &WaypointString) -> Option<schemas::Survey> {
+    // drops a survey for the whole fleet, e.g. once it is exhausted
+    pub async fn remove_survey(&self, waypoint: &WaypointString, signature: &str) {
         trace!("Remove Survey");
-        self.write()
-            .await
-            .automation_data
-            .surveys
-            .remove(waypoint)
-            .map(|surveys| surveys[0].to_owned())
+        if let Some(surveys) = self.write().await.automation_data.surveys.get_mut(waypoint) {
+            surveys.retain(|survey| survey.signature != signature);
+        }
     }
-    pub async fn create_survey(&self) -> Option<schemas::Survey> {
+    // surveys the ship's waypoint and shares the results with the fleet
+    pub async fn create_survey(&self) -> Option<Vec<schemas::Survey>> {
         trace!("Create Survey");
-
         let ship = self.clone_ship().await.unwrap();
-        let mut unlocked = self.write().await;
-        let survey = unlocked
-            .automation_data
-            .surveys
-            .get(&ship.nav.waypoint_symbol);
-
-        if survey.is_some() {
-            let survey = survey.unwrap();
-            if !survey.is_empty() {
-                Some(survey[0].clone())
-            // TODO:       ^^^^ maybe do something fancier here - should check if this is expired
-            } else {
-                drop(unlocked); // drops to prevent code duplication
-                                // or having to reimplement
-                self.remove_survey(&ship.nav.waypoint_symbol).await;
-                None
-            }
-        } else {
-            for mount in ship.mounts.iter() {
-                if mount.symbol == enums::ShipMount::MountSurveyorI
-                    || mount.symbol == enums::ShipMount::MountSurveyorIi
-                    || mount.symbol == enums::ShipMount::MountSurveyorIii
-                {
-                    let survey = unlocked
-                        .st_interface
-                        .create_survey(&self.ship_id)
-                        .await
-                        .expect("Failed to create Survey");
-
-                    let surveys = unlocked
-                        .automation_data
-                        .surveys
-                        .entry(ship.nav.waypoint_symbol)
-                        .and_modify(|vec| vec.append(&mut survey.data.surveys.clone()))
-                        .or_insert(survey.data.surveys);
-
-                    return Some(surveys[0].clone());
-                } else {
-                    return None;
-                }
-            }
-            None
-        }
-    }
-
-    pub async fn orbit_ship(&self) {
-        trace!("Orbit Ship");
-        let ship = match self
+        let response = self
             .read()
             .await
             .st_interface
-            .orbit_ship(&self.ship_id)
-            .await
-        {
-            Err(spacetraders::SpacetradersError::ShipInTransitError) => return,
-            Err(_) => {
-                error!("Unplanned error when trying to orbit");
-                return;
+            .create_survey(&self.ship_id)
+            .await;
+        let survey = match response {
+            Ok(survey) => survey.data,
+            Err(err) => {
+                error!("{} Failed to create Survey: {:?}", self.ship_id, err);
+                return None;
             }
-            Ok(ship) => ship,
         };
+        self.update_cooldown(&survey.cooldown).await;
 
         self.write()
             .await
             .automation_data
-            .ships
-            .get_mut(&self.ship_id)
-            .unwrap()
-            .nav = ship.data.nav;
-        self.wait_flight_duration().await;
-    }
-
-    pub async fn dock_ship(&self) {
-        trace!("Dock Ship");
-        let ship = self
-            .read()
-            .await
-            .st_interface
-            .dock_ship(&self.ship_id)
-            .await
-            .unwrap();
-        self.write()
-            .await
-            .automation_data
-            .ships
-            .get_mut(&self.ship_id)
-            .unwrap()
-            .nav = ship.data.nav;
-        self.wait_flight_duration().await;
+            .surveys
+            .entry(ship.nav.waypoint_symbol)
+            .or_default()
+            .extend(survey.surveys.iter().cloned());
+        Some(survey.surveys)
     }
 
     pub async fn get_waypoint(&self, waypoint: &WaypointString) -> schemas::Waypoint {
@@ -241,6 +178,10 @@ impl ShipAutomation {
                     .await
                     .unwrap()
                     .data;
+                unlocked
+                    .automation_data
+                    .store
+                    .record_waypoints(std::slice::from_ref(&new_waypoint));
                 if new_waypoint.chart.submitted_by.is_empty() {
                     new_waypoint
                 } else {
@@ -248,6 +189,10 @@ impl ShipAutomation {
                         .automation_data
                         .waypoints
                         .insert(new_waypoint.symbol.clone(), new_waypoint.clone());
+                    unlocked
+                        .automation_data
+                        .cache
+                        .store(Dataset::Waypoints, &unlocked.automation_data.waypoints);
                     new_waypoint
                 }
             }
@@ -262,7 +207,12 @@ impl ShipAutomation {
             .list_waypoints(system, false)
             .await
             .unwrap();
+        unlocked
+            .automation_data
+            .store
+            .record_waypoints(&waypoints.data);
         let mut return_vec = Vec::new();
+        let mut new_charts = false;
         for new_waypoint in waypoints.data.iter() {
             let waypoints = unlocked.automation_data.waypoints.clone();
 
@@ -277,109 +227,162 @@ impl ShipAutomation {
                             .waypoints
                             .insert(new_waypoint.symbol.clone(), new_waypoint.clone());
                         return_vec.push(new_waypoint.clone());
+                        new_charts = true;
                     }
                 }
             }
         }
+        if new_charts {
+            unlocked
+                .automation_data
+                .cache
+                .store(Dataset::Waypoints, &unlocked.automation_data.waypoints);
+        }
         return_vec
     }
 
-    pub async fn chart_waypoint(&self) {
-        trace!("Chart Waypoint");
-
-        let ship_location = self
+    // always fetches the latest market, the cached copy is kept for planning
+    pub async fn get_market(&self, waypoint: &schemas::Waypoint) -> Option<schemas::Market> {
+        trace!("Get Market");
+        let market = self
             .read()
             .await
-            .automation_data
-            .ships
-            .get(&self.ship_id)
-            .unwrap()
-            .nav
-            .waypoint_symbol
-            .clone();
-
-        let ship = self.read().await.automation_data.waypoints.clone();
-        if ship.get(&ship_location).is_none() {
-            let waypoint = self
-                .read()
-                .await
-                .st_interface
-                .get_waypoint(&ship_location.to_system(), &ship_location)
-                .await
-                .unwrap();
+            .st_interface
+            .get_market(&waypoint.system_symbol, &waypoint.symbol)
+            .await
+            .ok()?
+            .data;
 
-            if waypoint.data.chart.submitted_by.is_empty() {
-                let _ = self
-                    .read()
-                    .await
-                    .st_interface
-                    .create_chart(&self.ship_id)
-                    .await;
-            }
-        }
+        let mut unlocked = self.write().await;
+        unlocked
+            .automation_data
+            .store
+            .record_market(waypoint, &market);
+        unlocked
+            .automation_data
+            .markets
+            .insert(waypoint.symbol.clone(), market.clone());
+        unlocked
+            .automation_data
+            .cache
+            .store(Dataset::Markets, &unlocked.automation_data.markets);
+        Some(market)
     }
 
-    pub async fn wait_flight_duration(&self) {
-        trace!("Wait Durtation");
-        let local_time_to_stop: DateTime<Local> = self
+    pub async fn get_shipyard(&self, waypoint: &schemas::Waypoint) -> Option<schemas::Shipyard> {
+        trace!("Get Shipyard");
+        let shipyard = self
             .read()
             .await
-            .automation_data
-            .ships
-            .get(&self.ship_id)
-            .unwrap()
-            .nav
-            .route
-            .arrival
-            .into();
-        let local_time_now: DateTime<Local> = Local::now();
-        let duration: chrono::Duration = local_time_to_stop - local_time_now;
-
-        if duration.num_milliseconds() > 0 {
-            info!(
-                "{} is going to sleep for {} seconds",
-                self.read()
-                    .await
-                    .automation_data
-                    .ships
-                    .get(&self.ship_id)
-                    .unwrap()
-                    .symbol,
-                duration.num_seconds()
-            );
+            .st_interface
+            .get_shipyard(&waypoint.system_symbol, &waypoint.symbol)
+            .await
+            .ok()?
+            .data;
 
-            sleep(Duration::from_secs(
-                duration.num_seconds().try_into().unwrap(),
-            ))
-            .await;
-        }
+        let mut unlocked = self.write().await;
+        unlocked
+            .automation_data
+            .store
+            .record_shipyard(waypoint, &shipyard);
+        unlocked
+            .automation_data
+            .shipyards
+            .insert(waypoint.symbol.clone(), shipyard.clone());
+        unlocked
+            .automation_data
+            .cache
+            .store(Dataset::Shipyards, &unlocked.automation_data.shipyards);
+        Some(shipyard)
     }
 
-    pub async fn travel_waypoint(&self, waypoint: &str) -> Option<schemas::Ship> {
-        trace!("Travel Waypoint");
-        self.chart_waypoint().await;
-        let ship_id = &self.ship_id;
+    // charts the waypoint the ship is at, if nobody has yet
+    pub async fn chart_waypoint(&self) -> Option<schemas::Waypoint> {
+        trace!("Chart Waypoint");
 
-        let ship = self
+        let ship_location = self.clone_ship().await.unwrap().nav.waypoint_symbol;
+        // the store learns about every waypoint with its system, so this costs no request
+        let uncharted = self
             .read()
             .await
             .automation_data
-            .ships
-            .get(ship_id)
-            .unwrap()
-            .clone();
+            .store
+            .waypoint(&ship_location)
+            .is_some_and(|waypoint| !waypoint.charted);
+        if !uncharted {
+            return None;
+        }
 
-        self.get_fuel(ship.fuel.consumed.amount.try_into().unwrap())
+        let response = self
+            .read()
+            .await
+            .st_interface
+            .create_chart(&self.ship_id)
             .await;
+        match response {
+            Ok(response) => {
+                let waypoint = response.data.waypoint;
+                info!("{} charted {}", self.ship_id, waypoint.symbol.waypoint);
+                self.record_waypoints(std::slice::from_ref(&waypoint)).await;
+                Some(waypoint)
+            }
+            Err(SpacetradersError::WaypointChartedError) => {
+                info!("{} was charted by someone else", ship_location.waypoint);
+                self.get_waypoint(&ship_location).await;
+                None
+            }
+            Err(err) => {
+                error!(
+                    "{} failed to chart {}: {:?}",
+                    self.ship_id, ship_location.waypoint, err
+                );
+                None
+            }
+        }
+    }
 
-        if ship.nav.waypoint_symbol.waypoint != waypoint {
-            if ship.nav.status == enums::ShipNavStatus::Docked {
-                self.orbit_ship().await;
-            } else if ship.nav.status == enums::ShipNavStatus::InTransit {
-                self.wait_flight_duration().await;
-                self.orbit_ship().await;
+    // feeds waypoints we learned about from charts and scans into the caches
+    pub async fn record_waypoints(&self, waypoints: &[schemas::Waypoint]) {
+        trace!("Record Waypoints");
+        let mut unlocked = self.write().await;
+        unlocked.automation_data.store.record_waypoints(waypoints);
+        let mut new_charts = false;
+        for waypoint in waypoints {
+            // only charted waypoints are final, the rest gets looked up again
+            if !waypoint.chart.submitted_by.is_empty() {
+                unlocked
+                    .automation_data
+                    .waypoints
+                    .insert(waypoint.symbol.clone(), waypoint.clone());
+                new_charts = true;
             }
-            //TODO: consider fuel types here - eg stealth, drift
+        }
+        if new_charts {
+            unlocked
+                .automation_data
+                .cache
+                .store(Dataset::Waypoints, &unlocked.automation_data.waypoints);
+        }
+    }
+
+    pub async fn travel_waypoint(&self, waypoint: &WaypointString) -> Option<schemas::Ship> {
+        trace!("Travel Waypoint");
+        let ship_id = &self.ship_id;
+        let ship = self.clone_ship().await.unwrap();
+
+        if ship.nav.waypoint_symbol != *waypoint {
+            // drifting is slow but barely burns fuel, so a dry tank never strands us
+            let flight_mode = if fuel::prepare(self, waypoint).await {
+                enums::FlightMode::Cruise
+            } else {
+                info!(
+                    "{} is short of fuel and drifts to {}",
+                    ship_id, waypoint.waypoint
+                );
+                enums::FlightMode::Drift
+            };
+            self.ensure_orbit().await;
+            self.set_flight_mode(flight_mode).await;
             let temp_ship_data = self
                 .read()
                 .await
@@ -387,90 +390,183 @@ impl ShipAutomation {
                 .navigate_ship(
                     ship_id,
                     requests::NavigateShip {
-                        waypoint_symbol: waypoint.to_string(),
+                        waypoint_symbol: waypoint.waypoint.clone(),
                     },
                 )
                 .await;
 
-            if temp_ship_data.is_ok() {
-                let temp_ship_data = temp_ship_data.unwrap().data;
-
-                (
-                    self.write()
-                        .await
-                        .automation_data
-                        .ships
-                        .get_mut(ship_id)
-                        .unwrap()
-                        .nav,
-                    self.write()
-                        .await
-                        .automation_data
-                        .ships
-                        .get_mut(ship_id)
-                        .unwrap()
-                        .fuel,
-                ) = (temp_ship_data.nav, temp_ship_data.fuel);
+            if let Ok(temp_ship_data) = temp_ship_data {
+                let temp_ship_data = temp_ship_data.data;
+                self.update_nav(temp_ship_data.nav).await;
+                self.update_fuel(temp_ship_data.fuel).await;
 
-                self.wait_flight_duration().await;
+                self.wait_for_arrival().await;
 
                 self.chart_waypoint().await;
-
-                self.get_fuel(ship.fuel.consumed.amount.try_into().unwrap())
-                    .await;
             }
         }
         self.clone_ship().await
     }
 
-    // TODO: needs work
-    // should consider fuel prices and other locations
-    pub async fn get_fuel(&self, fuel_amount: i32) {
+    async fn set_flight_mode(&self, flight_mode: enums::FlightMode) {
         let ship = self.clone_ship().await.unwrap();
-        let waypoint = self.get_waypoint(&ship.nav.waypoint_symbol).await;
+        if ship.nav.flight_mode == flight_mode {
+            return;
+        }
+        let response = self
+            .read()
+            .await
+            .st_interface
+            .patch_ship_nav(&self.ship_id, requests::PatchShipNav { flight_mode })
+            .await;
+        match response {
+            Ok(response) => self.update_nav(response.data).await,
+            Err(err) => error!("{} failed to change flight mode: {:?}", self.ship_id, err),
+        }
+    }
 
-        for r#trait in waypoint.traits.iter() {
-            if r#trait.symbol == enums::WaypointTrait::Marketplace {
-                let market = self
-                    .read()
-                    .await
-                    .st_interface
-                    .get_market(&waypoint.system_symbol, &waypoint.symbol)
-                    .await
-                    .unwrap()
-                    .data;
-                for tradegood in market.exports.iter() {
-                    if tradegood.symbol == enums::TradeSymbol::Fuel
-                        && ship.fuel.current != ship.fuel.capacity
-                    {
-                        if ship.nav.status == enums::ShipNavStatus::Docked {
-                            for _ in 0..((ship.fuel.capacity as f32 / 100_f32).ceil() as u32) {
-                                let _ = self
-                                    .read()
-                                    .await
-                                    .st_interface
-                                    .refuel_ship(
-                                        &self.ship_id,
-                                        Ok(requests::RefuelShip { units: fuel_amount }),
-                                    )
-                                    .await;
-                            }
-                        } else if ship.nav.status == enums::ShipNavStatus::InOrbit {
-                            self.dock_ship().await;
-                            let _ = self
-                                .read()
-                                .await
-                                .st_interface
-                                .refuel_ship(
-                                    &self.ship_id,
-                                    Ok(requests::RefuelShip { units: fuel_amount }),
-                                )
-                                .await;
-                            self.orbit_ship().await;
-                        }
-                        return;
-                    }
-                }
+    // the ship has to be docked at a market that buys the good
+    pub async fn sell_cargo(
+        &self,
+        symbol: &enums::TradeSymbol,
+        units: i32,
+    ) -> Option<schemas::MarketTransaction> {
+        trace!("Sell Cargo");
+        let response = self
+            .read()
+            .await
+            .st_interface
+            .sell_cargo(
+                &self.ship_id,
+                requests::SellCargo {
+                    symbol: symbol.clone(),
+                    units,
+                },
+            )
+            .await;
+        match response {
+            Ok(response) => {
+                let response = response.data;
+                info!(
+                    "{} sold {} {:?} for {}",
+                    self.ship_id, units, symbol, response.transaction.total_price
+                );
+                self.update_cargo(response.cargo).await;
+                self.record_transaction(LedgerEntry::market(
+                    Activity::Sale,
+                    &response.transaction,
+                    response.agent.credits,
+                ))
+                .await;
+                Some(response.transaction)
+            }
+            Err(err) => {
+                error!("{} failed to sell {:?}: {:?}", self.ship_id, symbol, err);
+                None
+            }
+        }
+    }
+
+    // the ship has to be docked at a market that sells the good
+    pub async fn purchase_cargo(
+        &self,
+        symbol: &enums::TradeSymbol,
+        units: i32,
+    ) -> Option<schemas::MarketTransaction> {
+        trace!("Purchase Cargo");
+        let response = self
+            .read()
+            .await
+            .st_interface
+            .purchase_cargo(
+                &self.ship_id,
+                requests::PurchaseCargo {
+                    symbol: symbol.clone(),
+                    units,
+                },
+            )
+            .await;
+        match response {
+            Ok(response) => {
+                let response = response.data;
+                info!(
+                    "{} bought {} {:?} for {}",
+                    self.ship_id, units, symbol, response.transaction.total_price
+                );
+                self.update_cargo(response.cargo).await;
+                self.record_transaction(LedgerEntry::market(
+                    Activity::Purchase,
+                    &response.transaction,
+                    response.agent.credits,
+                ))
+                .await;
+                Some(response.transaction)
+            }
+            Err(err) => {
+                error!("{} failed to buy {:?}: {:?}", self.ship_id, symbol, err);
+                None
+            }
+        }
+    }
+
+    // markets only trade so much at once, returns how many units were bought
+    pub async fn purchase_cargo_in_chunks(
+        &self,
+        symbol: &enums::TradeSymbol,
+        units: i32,
+        trade_volume: u32,
+    ) -> i32 {
+        trace!("Purchase Cargo In Chunks");
+        let mut bought = 0;
+        while bought < units {
+            let chunk = (units - bought).min(trade_volume.max(1) as i32);
+            if self.purchase_cargo(symbol, chunk).await.is_none() {
+                break;
+            }
+            bought += chunk;
+        }
+        bought
+    }
+
+    // the ship has to be docked at the contract's destination
+    pub async fn deliver_contract(
+        &self,
+        contract_id: &str,
+        symbol: &enums::TradeSymbol,
+        units: i32,
+    ) -> Option<schemas::Contract> {
+        trace!("Deliver Contract");
+        let response = self
+            .read()
+            .await
+            .st_interface
+            .deliver_contract(
+                contract_id,
+                requests::DeliverCargoToContract {
+                    ship_symbol: self.ship_id.clone(),
+                    trade_symbol: symbol.clone(),
+                    units: units.into(),
+                },
+            )
+            .await;
+        match response {
+            Ok(response) => {
+                let response = response.data;
+                info!(
+                    "{} delivered {} {:?} to contract {}",
+                    self.ship_id, units, symbol, contract_id
+                );
+                self.update_cargo(response.cargo).await;
+                self.add_contract(contract_id, response.contract.clone())
+                    .await;
+                Some(response.contract)
+            }
+            Err(err) => {
+                error!(
+                    "{} failed to deliver {:?} to contract {}: {:?}",
+                    self.ship_id, symbol, contract_id, err
+                );
+                None
             }
         }
     }
@@ -478,11 +574,60 @@ impl ShipAutomation {
     pub fn euclidean_distance(&self, x1: i32, y1: i32, x2: i32, y2: i32) -> u64 {
         trace!("ship_data Euclidean Distance");
         let distance: f64 =
-            ((x1 as f64 - y1 as f64).powi(2) + (x2 as f64 - y2 as f64).powi(2)).sqrt();
+            ((x1 as f64 - x2 as f64).powi(2) + (y1 as f64 - y2 as f64).powi(2)).sqrt();
         let distance: u64 = distance.round() as u64;
         distance
     }
 
+    // jumps through the known gates to the system, false if we don't know
+    // the way there or a jump fails
+    pub async fn jump_to_system(&self, system: &SystemString, max_jumps: usize) -> bool {
+        trace!("Jump To System");
+        let from = self.clone_ship().await.unwrap().nav.system_symbol;
+        let route = self
+            .read()
+            .await
+            .automation_data
+            .store
+            .jump_route(&from, system, max_jumps);
+        let Some(route) = route else {
+            info!("{} knows no way to {}", self.ship_id, system.system);
+            return false;
+        };
+        for (gate, next) in route {
+            if !self.ensure_orbit_at(&gate).await {
+                return false;
+            }
+            self.wait_for_cooldown().await;
+            let response = self
+                .read()
+                .await
+                .st_interface
+                .jump_ship(
+                    &self.ship_id,
+                    requests::JumpShip {
+                        system_symbol: next.system.clone(),
+                    },
+                )
+                .await;
+            match response {
+                Ok(response) => {
+                    info!("{} jumped to {}", self.ship_id, next.system);
+                    self.update_nav(response.data.nav).await;
+                    self.update_cooldown(&response.data.cooldown).await;
+                }
+                Err(err) => {
+                    error!(
+                        "{} failed to jump to {}: {:?}",
+                        self.ship_id, next.system, err
+                    );
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
     pub async fn travel_system(&self, waypoint: &str) {
         trace!("travel");
 
@@ -499,9 +644,7 @@ impl ShipAutomation {
         if ship.nav.waypoint_symbol.waypoint != waypoint {
             // there is also a case where the ship is in transit and neither docked or there
 
-            if ship.nav.status == enums::ShipNavStatus::Docked {
-                self.orbit_ship().await;
-            }
+            self.ensure_orbit().await;
 
             // depending on whether there is a warp drive or jump drive determines the endpoint to use
             // also ensure to check if there is a jump gate
@@ -523,29 +666,36 @@ impl ShipAutomation {
     pub async fn extract_resources(
         &self,
     ) -> Option<(schemas::ShipCargo, schemas::Cooldown, schemas::Extraction)> {
-        let survey = self.create_survey().await;
-        let ship = match self
-            .read()
-            .await
-            .st_interface
-            .extract_resources(&self.ship_id, survey)
-            .await
-        {
-            Ok(data) => Some(data.data),
-            Err(_) => {
-                error!("{} Failed to extract resources", self.ship_id);
-                None
+        let waypoint = self.clone_ship().await.unwrap().nav.waypoint_symbol;
+        let mut survey = self.best_survey().await;
+        let ship = loop {
+            let response = self
+                .read()
+                .await
+                .st_interface
+                .extract_resources(&self.ship_id, survey.clone())
+                .await;
+            match response {
+                Ok(data) => break Some(data.data),
+                // someone else used it up first, drop it and mine without one
+                Err(
+                    SpacetradersError::ShipSurveyExhaustedError
+                    | SpacetradersError::ShipSurveyExpirationError,
+                ) if survey.is_some() => {
+                    let signature = survey.take().unwrap().signature;
+                    info!("{} survey {} is used up", self.ship_id, signature);
+                    self.remove_survey(&waypoint, &signature).await;
+                }
+                Err(_) => {
+                    error!("{} Failed to extract resources", self.ship_id);
+                    break None;
+                }
             }
         };
 
         if let Some(ship) = ship {
-            self.write()
-                .await
-                .automation_data
-                .ships
-                .get_mut(&self.ship_id)
-                .unwrap()
-                .cargo = ship.cargo.clone();
+            self.update_cargo(ship.cargo.clone()).await;
+            self.update_cooldown(&ship.cooldown).await;
             let (cooldown, extraction) = (ship.cooldown, ship.extraction);
             Some((ship.cargo, cooldown, extraction))
         } else {
@@ -553,28 +703,20 @@ impl ShipAutomation {
         }
     }
     // pub async fn pathfind(self)
-    // pub async fn buy_mount(self, module: enums::ShipModule) {
-    //     let ship = self.clone_ship().await.unwrap();
-    //     for r#trait in self
-    //         .get_waypoint(&ship.nav.waypoint_symbol)
-    //         .await
-    //         .traits
-    //         .iter()
-    //     {
-    //         if r#trait == enums::WaypointTrait::Marketplace {
-    //             self.buy_from_marketplace(module).await
-    //         }
-    //     }
-    // }
-    // pub async fn buy_from_marketplace(self, trade_good: enums::ShipModule) { // enums::TradeSymbol
-    //                                                                          // TODO: need to write an adapter to convert enums::ShipModule to enums::TradeSymbol
-    //                                                                          // This asumes you are already at the marketplace
-    //     self.st_interface.lock().await.spacetraders.
-    // }
     // pub async fn get_market()
     // TODO: cache market data
 }
 
+// every unit of fuel bought at a market fills 100 units of the tank
+pub const FUEL_PER_MARKET_UNIT: f64 = 100.0;
+// how much slower cruising is than the engine's raw speed
+const CRUISE_MULTIPLIER: f64 = 25.0;
+
+// seconds to cruise the distance at the engine's speed
+pub fn travel_seconds(distance: f64, speed: u32) -> f64 {
+    distance.round() * (CRUISE_MULTIPLIER / speed.max(1) as f64) + 15.0
+}
+
 pub fn sort_distances(
     mut distances: Vec<(&schemas::Waypoint, u64)>,
 ) -> Vec<(&schemas::Waypoint, u64)> {
@@ -0,0 +1,361 @@
+use super::{
+    behavior::{self, ShipBehavior},
+    func::ShipAutomation,
+    miner, seller,
+    state::ShipState,
+};
+use spacetraders::{
+    enums, requests,
+    responses::schemas::{Contract, Ship},
+    SystemString, WaypointString,
+};
+
+use async_trait::async_trait;
+use log::{error, info, trace, warn};
+use std::collections::HashMap;
+use tokio::{
+    sync::mpsc,
+    time::{sleep, Duration},
+};
+
+// a hauler heads off to unload once its hold is this full
+const FULL_ENOUGH: f64 = 0.9;
+
+// Flies between the mining sites and takes the miners' cargo so they can keep
+// extracting, then delivers contract goods and sells whatever is left.
+#[derive(Debug)]
+pub struct Hauler;
+#[async_trait]
+impl ShipBehavior for Hauler {
+    fn name(&self) -> &'static str {
+        "hauler"
+    }
+    async fn run(&self, ship_automation: &mut ShipAutomation, _channel: &mpsc::Sender<Ship>) {
+        let ship = ship_automation.clone_ship().await.unwrap();
+        if ship.cargo.units as f64 >= ship.cargo.capacity as f64 * FULL_ENOUGH {
+            unload(ship_automation).await;
+            return;
+        }
+
+        // reserved goods sitting on a miner come first
+        if let Some(miner) = claim_reserved(ship_automation, &ship).await {
+            collect(ship_automation, &miner, |_| true).await;
+            release_claims(ship_automation).await;
+            return;
+        }
+
+        match next_miner(ship_automation, &ship, |_| true).await {
+            Some(miner) => collect(ship_automation, &miner, |_| true).await,
+            None if ship.cargo.units > 0 => unload(ship_automation).await,
+            None => sleep(Duration::from_secs(30)).await,
+        }
+    }
+}
+
+// claims the miner in our system holding the most reserved goods that no
+// other hauler is picking up already
+async fn claim_reserved(ship_automation: &ShipAutomation, hauler: &Ship) -> Option<Ship> {
+    trace!("Claim Reserved");
+    let mut unlocked = ship_automation.write().await;
+    let data = &mut unlocked.automation_data;
+    let miner = data
+        .ships
+        .values()
+        .filter(|ship| ship.symbol != hauler.symbol)
+        .filter(|ship| ship.nav.system_symbol == hauler.nav.system_symbol)
+        .filter(|ship| matches!(ShipState::from_nav(&ship.nav), ShipState::InOrbit(_)))
+        .filter(|ship| data.behaviors.behavior_for(ship).name() == behavior::Miner.name())
+        .filter(|ship| {
+            !data
+                .reservations
+                .claimed_by_other(&hauler.symbol, &ship.symbol)
+        })
+        .map(|ship| {
+            let reserved: i32 = ship
+                .cargo
+                .inventory
+                .iter()
+                .map(|item| {
+                    item.units
+                        .min(data.reservations.contract_reserved(&item.symbol))
+                })
+                .sum();
+            (reserved, ship)
+        })
+        .filter(|(reserved, _)| *reserved > 0)
+        .max_by_key(|(reserved, _)| *reserved)
+        .map(|(_, ship)| ship.clone())?;
+
+    if !data.reservations.claim(&hauler.symbol, &miner.symbol) {
+        return None;
+    }
+    info!(
+        "{} claims the reserved goods on {}",
+        hauler.symbol, miner.symbol
+    );
+    Some(miner)
+}
+
+async fn release_claims(ship_automation: &ShipAutomation) {
+    ship_automation
+        .write()
+        .await
+        .automation_data
+        .reservations
+        .release_claims(&ship_automation.ship_id);
+}
+
+// true if a hauler will come and pick up cargo from miners in this system
+pub async fn hauler_in_system(ship_automation: &ShipAutomation, system: &SystemString) -> bool {
+    let unlocked = ship_automation.read().await;
+    unlocked.automation_data.ships.values().any(|ship| {
+        ship.nav.system_symbol == *system
+            && unlocked.automation_data.behaviors.behavior_for(ship).name() == Hauler.name()
+    })
+}
+
+// the miner in our system sitting on the most of the goods we want
+pub(crate) async fn next_miner(
+    ship_automation: &ShipAutomation,
+    hauler: &Ship,
+    wanted: fn(&enums::TradeSymbol) -> bool,
+) -> Option<Ship> {
+    trace!("Next Miner");
+    let unlocked = ship_automation.read().await;
+    unlocked
+        .automation_data
+        .ships
+        .values()
+        .filter(|ship| ship.symbol != hauler.symbol)
+        .filter(|ship| ship.nav.system_symbol == hauler.nav.system_symbol)
+        .filter(|ship| matches!(ShipState::from_nav(&ship.nav), ShipState::InOrbit(_)))
+        .filter(|ship| {
+            unlocked.automation_data.behaviors.behavior_for(ship).name() == behavior::Miner.name()
+        })
+        // another hauler is on its way for this one
+        .filter(|ship| {
+            !unlocked
+                .automation_data
+                .reservations
+                .claimed_by_other(&hauler.symbol, &ship.symbol)
+        })
+        .map(|ship| (wanted_units(ship, wanted), ship))
+        .filter(|(units, _)| *units > 0)
+        .max_by_key(|(units, _)| *units)
+        .map(|(_, ship)| ship.clone())
+}
+
+pub(crate) async fn collect(
+    ship_automation: &ShipAutomation,
+    miner: &Ship,
+    wanted: fn(&enums::TradeSymbol) -> bool,
+) {
+    trace!("Collect Cargo");
+    if !ship_automation
+        .ensure_orbit_at(&miner.nav.waypoint_symbol)
+        .await
+    {
+        return;
+    }
+
+    // the miner kept working while we were on our way
+    let miner = match ship_automation.clone_ships().await.remove(&miner.symbol) {
+        Some(miner) if ShipState::from_nav(&miner.nav) == ship_automation.state().await => miner,
+        _ => {
+            info!(
+                "{} missed {}, it has moved on",
+                ship_automation.ship_id, miner.symbol
+            );
+            return;
+        }
+    };
+
+    for item in miner
+        .cargo
+        .inventory
+        .iter()
+        .filter(|item| wanted(&item.symbol))
+    {
+        let hauler = ship_automation.clone_ship().await.unwrap();
+        let units = item.units.min(hauler.cargo.capacity - hauler.cargo.units);
+        if units <= 0 {
+            break;
+        }
+
+        let response = ship_automation
+            .read()
+            .await
+            .st_interface
+            .transfer_cargo(
+                &miner.symbol,
+                requests::TransferCargo {
+                    trade_symbol: item.symbol.clone(),
+                    units,
+                    ship_symbol: ship_automation.ship_id.clone(),
+                },
+            )
+            .await;
+        match response {
+            Ok(response) => {
+                info!(
+                    "{} took {} {:?} from {}",
+                    ship_automation.ship_id, units, item.symbol, miner.symbol
+                );
+                if let Some(miner) = ship_automation
+                    .write()
+                    .await
+                    .automation_data
+                    .ships
+                    .get_mut(&miner.symbol)
+                {
+                    miner.cargo = response.data.cargo;
+                }
+                refresh_cargo(ship_automation).await;
+            }
+            Err(err) => {
+                error!(
+                    "{} failed to take {:?} from {}: {:?}",
+                    ship_automation.ship_id, item.symbol, miner.symbol, err
+                );
+                break;
+            }
+        }
+    }
+}
+
+// transfers only return the sender's cargo, so ask for ours
+async fn refresh_cargo(ship_automation: &ShipAutomation) {
+    let cargo = ship_automation
+        .read()
+        .await
+        .st_interface
+        .get_ship_cargo(&ship_automation.ship_id)
+        .await;
+    if let Ok(cargo) = cargo {
+        ship_automation.update_cargo(cargo.data).await;
+    }
+}
+
+pub(crate) async fn unload(ship_automation: &mut ShipAutomation) {
+    trace!("Unload");
+    deliver_contract_goods(ship_automation).await;
+    sell_at_best_market(ship_automation).await;
+
+    // anything nobody has a known price for goes the miners' way
+    if ship_automation.clone_ship().await.unwrap().cargo.units > 0 {
+        miner::sell_mining_cargo(ship_automation).await;
+    }
+}
+
+async fn deliver_contract_goods(ship_automation: &ShipAutomation) {
+    trace!("Deliver Contract Goods");
+    let contracts = ship_automation
+        .read()
+        .await
+        .st_interface
+        .list_contracts(false)
+        .await;
+    let contracts = match contracts {
+        Ok(contracts) => contracts.data,
+        Err(_) => {
+            error!("{} Failed to get Contracts", ship_automation.ship_id);
+            return;
+        }
+    };
+
+    for contract in contracts.iter() {
+        ship_automation
+            .add_contract(&contract.id, contract.clone())
+            .await;
+    }
+
+    let ship = ship_automation.clone_ship().await.unwrap();
+    for delivery in plan_deliveries(&ship, &contracts) {
+        if ship_automation
+            .ensure_docked_at(&delivery.destination)
+            .await
+        {
+            ship_automation
+                .deliver_contract(&delivery.contract, &delivery.symbol, delivery.units)
+                .await;
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Delivery {
+    pub contract: String,
+    pub symbol: enums::TradeSymbol,
+    pub destination: WaypointString,
+    pub units: i32,
+}
+
+// what of the hold goes to which open contract, the rest is left to be sold
+pub(crate) fn plan_deliveries(ship: &Ship, contracts: &[Contract]) -> Vec<Delivery> {
+    let mut left: HashMap<enums::TradeSymbol, i32> = HashMap::new();
+    for item in ship.cargo.inventory.iter() {
+        *left.entry(item.symbol.clone()).or_default() += item.units;
+    }
+
+    let mut deliveries = Vec::new();
+    for contract in contracts
+        .iter()
+        .filter(|contract| contract.accepted && !contract.fulfilled)
+    {
+        for good in contract.terms.deliver.iter() {
+            let Some(symbol) = &good.trade_symbol else {
+                continue;
+            };
+            let Some(held) = left.get_mut(symbol) else {
+                continue;
+            };
+            let units = (*held).min(good.remaining_units() as i32);
+            if units <= 0 {
+                continue;
+            }
+            let Some(destination) = waypoint_string(&good.destination_symbol) else {
+                warn!("Invalid contract destination {}", good.destination_symbol);
+                continue;
+            };
+            *held -= units;
+            deliveries.push(Delivery {
+                contract: contract.id.clone(),
+                symbol: symbol.clone(),
+                destination,
+                units,
+            });
+        }
+    }
+    deliveries
+}
+
+async fn sell_at_best_market(ship_automation: &mut ShipAutomation) {
+    trace!("Sell At Best Market");
+    let inventory = ship_automation.clone_ship().await.unwrap().cargo.inventory;
+    for item in inventory.iter() {
+        let ship = ship_automation.clone_ship().await.unwrap();
+        seller::sell_item(ship_automation, &ship, item).await;
+    }
+}
+
+fn wanted_units(ship: &Ship, wanted: fn(&enums::TradeSymbol) -> bool) -> i32 {
+    ship.cargo
+        .inventory
+        .iter()
+        .filter(|item| wanted(&item.symbol))
+        .map(|item| item.units)
+        .sum()
+}
+
+pub(crate) fn held_units(ship: &Ship, symbol: &enums::TradeSymbol) -> i32 {
+    ship.cargo
+        .inventory
+        .iter()
+        .filter(|item| item.symbol == *symbol)
+        .map(|item| item.units)
+        .sum()
+}
+
+pub(crate) fn waypoint_string(symbol: &str) -> Option<WaypointString> {
+    serde_json::from_value(serde_json::Value::String(symbol.to_string())).ok()
+}
@@ -0,0 +1,251 @@
+use super::Automation;
+use spacetraders::{
+    enums,
+    responses::schemas::{MarketTransaction, ShipModificationTransaction, ShipyardTransaction},
+};
+
+use chrono::{DateTime, Local};
+use log::{trace, warn};
+use std::{io, str::FromStr};
+
+// What credits changed hands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Activity {
+    Sale,
+    Purchase,
+    ShipPurchase,
+    Refuel,
+    Contract,
+    Refit,
+}
+impl Activity {
+    pub const ALL: [Activity; 6] = [
+        Activity::Sale,
+        Activity::Purchase,
+        Activity::ShipPurchase,
+        Activity::Refuel,
+        Activity::Contract,
+        Activity::Refit,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Activity::Sale => "SALE",
+            Activity::Purchase => "PURCHASE",
+            Activity::ShipPurchase => "SHIP_PURCHASE",
+            Activity::Refuel => "REFUEL",
+            Activity::Contract => "CONTRACT",
+            Activity::Refit => "REFIT",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Activity> {
+        Activity::ALL
+            .into_iter()
+            .find(|activity| activity.as_str() == text)
+    }
+}
+impl FromStr for Activity {
+    type Err = String;
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Activity::parse(&text.to_uppercase()).ok_or_else(|| format!("Unknown activity {text}"))
+    }
+}
+
+// One change to our credits, income positive and spending negative.
+#[derive(Debug, Clone)]
+pub struct LedgerEntry {
+    pub recorded_at: DateTime<Local>,
+    pub ship: String,
+    pub activity: Activity,
+    // the trade good, ship type or contract the credits were for
+    pub good: String,
+    pub units: i32,
+    pub price_per_unit: f64,
+    pub total: f64,
+    // the agent's credits right after, as the server reported them
+    pub balance: f64,
+}
+impl LedgerEntry {
+    // sales earn credits, everything else bought at a market costs them
+    pub fn market(activity: Activity, transaction: &MarketTransaction, balance: f64) -> Self {
+        let total = match activity {
+            Activity::Sale => transaction.total_price,
+            _ => -transaction.total_price,
+        };
+        LedgerEntry {
+            recorded_at: transaction.timestamp,
+            ship: transaction.ship_symbol.clone(),
+            activity,
+            good: transaction.trade_symbol.clone(),
+            units: transaction.units as i32,
+            price_per_unit: transaction.price_per_unit,
+            total,
+            balance,
+        }
+    }
+
+    pub fn ship_purchase(
+        ship: &str,
+        ship_type: &enums::ShipType,
+        transaction: &ShipyardTransaction,
+        balance: f64,
+    ) -> Self {
+        let ship_type = match serde_json::to_value(ship_type) {
+            Ok(serde_json::Value::String(text)) => text,
+            _ => format!("{ship_type:?}"),
+        };
+        LedgerEntry {
+            recorded_at: Local::now(),
+            ship: ship.to_string(),
+            activity: Activity::ShipPurchase,
+            good: ship_type,
+            units: 1,
+            price_per_unit: transaction.price,
+            total: -transaction.price,
+            balance,
+        }
+    }
+
+    pub fn refit(transaction: &ShipModificationTransaction, balance: f64) -> Self {
+        LedgerEntry {
+            recorded_at: Local::now(),
+            ship: transaction.ship_symbol.clone(),
+            activity: Activity::Refit,
+            good: transaction.trade_symbol.clone(),
+            units: 1,
+            price_per_unit: transaction.total_price,
+            total: -transaction.total_price,
+            balance,
+        }
+    }
+
+    pub fn contract(ship: &str, contract_id: &str, payment: f64, balance: f64) -> Self {
+        LedgerEntry {
+            recorded_at: Local::now(),
+            ship: ship.to_string(),
+            activity: Activity::Contract,
+            good: contract_id.to_string(),
+            units: 1,
+            price_per_unit: payment,
+            total: payment,
+            balance,
+        }
+    }
+}
+
+// what a ship or an activity earned and spent
+#[derive(Debug, Clone, Default)]
+pub struct ProfitAndLoss {
+    pub income: f64,
+    pub spending: f64,
+    pub transactions: usize,
+}
+impl ProfitAndLoss {
+    pub fn profit(&self) -> f64 {
+        self.income - self.spending
+    }
+}
+
+// Which ledger entries to look at, everything is let through when unset.
+#[derive(Debug, Clone, Default)]
+pub struct LedgerFilter {
+    pub since: Option<DateTime<Local>>,
+    pub until: Option<DateTime<Local>>,
+    pub ship: Option<String>,
+    pub activity: Option<Activity>,
+}
+impl LedgerFilter {
+    pub fn since(since: DateTime<Local>) -> Self {
+        LedgerFilter {
+            since: Some(since),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    JsonLines,
+}
+impl FromStr for ExportFormat {
+    type Err = String;
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.to_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "json" | "jsonl" => Ok(ExportFormat::JsonLines),
+            _ => Err(format!("Unknown export format {text}, use csv or jsonl")),
+        }
+    }
+}
+
+const CSV_HEADER: &str = "recorded_at,ship,activity,good,units,price_per_unit,total,balance";
+
+// writes the entries out for notebooks and spreadsheets, one line per entry
+pub fn export(
+    entries: &[LedgerEntry],
+    format: ExportFormat,
+    out: &mut impl io::Write,
+) -> io::Result<()> {
+    trace!("Export Ledger");
+    if format == ExportFormat::Csv {
+        writeln!(out, "{CSV_HEADER}")?;
+    }
+    for entry in entries.iter() {
+        match format {
+            ExportFormat::Csv => writeln!(
+                out,
+                "{},{},{},{},{},{},{},{}",
+                entry.recorded_at.to_rfc3339(),
+                csv_field(&entry.ship),
+                entry.activity.as_str(),
+                csv_field(&entry.good),
+                entry.units,
+                entry.price_per_unit,
+                entry.total,
+                entry.balance
+            )?,
+            ExportFormat::JsonLines => writeln!(
+                out,
+                "{}",
+                serde_json::json!({
+                    "recorded_at": entry.recorded_at.to_rfc3339(),
+                    "ship": entry.ship,
+                    "activity": entry.activity.as_str(),
+                    "good": entry.good,
+                    "units": entry.units,
+                    "price_per_unit": entry.price_per_unit,
+                    "total": entry.total,
+                    "balance": entry.balance,
+                })
+            )?,
+        }
+    }
+    Ok(())
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+impl Automation {
+    // books the entry and takes the agent's balance over our own estimate
+    pub fn record_transaction(&mut self, entry: LedgerEntry) {
+        trace!("Record Transaction");
+        let expected = self.credits + entry.total;
+        if (expected - entry.balance).abs() >= 1.0 {
+            warn!(
+                "Credits were off by {:.0}, the agent has {}",
+                entry.balance - expected,
+                entry.balance
+            );
+        }
+        self.credits = entry.balance;
+        self.store.record_ledger_entry(&entry);
+    }
+}
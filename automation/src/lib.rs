@@ -1,26 +1,45 @@
 use spacetraders::{
-    enums::{ShipRole::*, ShipType::*},
     responses::schemas::{self, Contract, Ship},
     SpaceTraders, WaypointString,
 };
 
-pub mod admin;
+pub mod behavior;
 pub mod cache;
 pub mod contractor;
 pub mod explorer;
+pub mod fuel;
 mod func;
+pub mod hauler;
+pub mod ledger;
+pub mod loadout;
 mod miner;
-use cache::AllEuclideanDistances;
-use func::{SharedAutomationData, ShipAutomation};
-
-use chrono::{Duration, Local};
+pub mod planner;
+pub mod prober;
+pub mod refinery;
+pub mod reservations;
+pub mod scheduler;
+pub mod seller;
+pub mod state;
+pub mod store;
+pub mod surveyor;
+#[cfg(test)]
+mod tests;
+pub mod trader;
+use behavior::BehaviorRegistry;
+use cache::{AllEuclideanDistances, Cache};
+use func::SharedAutomationData;
+pub use func::ShipAutomation;
+use reservations::CargoReservations;
+use scheduler::MarketScheduler;
+use store::Store;
+
+use chrono::{DateTime, Duration, Local};
 use log::{info, trace};
 use std::{collections::HashMap, sync::Arc};
 use tokio::{
     runtime::Builder,
     sync::{mpsc, RwLock},
     task::JoinHandle,
-    time::{self, sleep},
 };
 
 #[derive(Debug)]
@@ -30,9 +49,17 @@ pub struct Automation {
     pub contracts: HashMap<String, Contract>,
     pub surveys: HashMap<WaypointString, Vec<schemas::Survey>>,
     pub waypoints: HashMap<WaypointString, schemas::Waypoint>,
-    // TODO: definatly cache market stuff to optimize refuel func
+    pub markets: HashMap<WaypointString, schemas::Market>,
+    pub shipyards: HashMap<WaypointString, schemas::Shipyard>,
     pub credits: f64,
+    // when each ship's extraction/survey/refine cooldown runs out
+    pub cooldowns: HashMap<String, DateTime<Local>>,
     pub euclidean_distances: Vec<AllEuclideanDistances>,
+    pub cache: Cache,
+    pub store: Store,
+    pub market_scheduler: MarketScheduler,
+    pub reservations: CargoReservations,
+    pub behaviors: BehaviorRegistry,
 }
 
 pub async fn ship_handler(st_interface: SpaceTraders, mut automation_data: Automation) {
@@ -62,6 +89,7 @@ pub async fn ship_handler(st_interface: SpaceTraders, mut automation_data: Autom
     // listens for new ship purchases and spawns new task to deal with them
     let duration = Duration::minutes(1);
     let mut last_print = Local::now();
+    let started = last_print;
     loop {
         if let Ok(msg) = rx.try_recv() {
             let ship_automation = ShipAutomation::new(shared_data.clone(), msg.symbol.as_str());
@@ -104,64 +132,63 @@ pub async fn ship_handler(st_interface: SpaceTraders, mut automation_data: Autom
         let now = Local::now();
         if now >= last_print + duration {
             last_print = now;
+
+            // a reset outdates everything we know, the cache and store throw it away
+            let (mut cache, st_interface) = {
+                let unlocked = shared_data.read().await;
+                (
+                    unlocked.automation_data.cache.clone(),
+                    unlocked.st_interface.clone(),
+                )
+            };
+            if cache.check_reset(&st_interface).await {
+                let mut unlocked = shared_data.write().await;
+                let automation_data = &mut unlocked.automation_data;
+                automation_data.store = Store::open(&cache);
+                automation_data.waypoints.clear();
+                automation_data.markets.clear();
+                automation_data.shipyards.clear();
+                automation_data.cache = cache;
+            }
+
+            let unlocked = shared_data.read().await;
+            let profits = unlocked.automation_data.store.profit_by_ship(started);
             println!("Current Ships:");
-            for (ship_id, ship) in shared_data.read().await.automation_data.ships.iter() {
-                println!("ID: {ship_id}, Role: {:?}", ship.registration.role);
+            for (ship_id, ship) in unlocked.automation_data.ships.iter() {
+                println!(
+                    "ID: {ship_id}, Role: {:?}, Profit: {:.0}",
+                    ship.registration.role,
+                    profits.get(ship_id).map_or(0.0, |pnl| pnl.profit())
+                );
+            }
+            println!("Current Credits: {}", unlocked.automation_data.credits);
+            for (activity, pnl) in unlocked.automation_data.store.profit_by_activity(started) {
+                println!(
+                    "{}: +{:.0} -{:.0} over {} transactions",
+                    activity.as_str(),
+                    pnl.income,
+                    pnl.spending,
+                    pnl.transactions
+                );
             }
-            println!(
-                "Current Credits: {}",
-                shared_data.read().await.automation_data.credits
-            );
         }
     }
 }
 
-pub async fn ship_duty(ship_automation: ShipAutomation, channel: mpsc::Sender<Ship>) {
+pub async fn ship_duty(mut ship_automation: ShipAutomation, channel: mpsc::Sender<Ship>) {
     trace!("Ship Handler");
 
-    let role = &ship_automation
-        .clone_ship()
-        .await
-        .unwrap()
-        .registration
-        .role;
-
     // TODO: checks if ship is under producing and parks it
 
-    match role {
-        Fabricator => todo!(),
-        Harvester => todo!(),
-        Hauler => todo!(),
-        Interceptor => todo!(),
-        Excavator => miner_loop(ship_automation, channel).await,
-        Transport => todo!(),
-        Repair => todo!(),
-        Surveyor => todo!(),
-        Command => explorer_loop(ship_automation, channel).await,
-        Carrier => todo!(),
-        Patrol => todo!(),
-        Satellite => explorer_loop(ship_automation, channel).await,
-        Explorer => explorer_loop(ship_automation, channel).await,
-        Refinery => todo!(),
-    };
-}
-
-async fn contractor_loop(ship_automation: ShipAutomation, channel: mpsc::Sender<Ship>) {
-    loop {
-        admin::admin_stuff(&ship_automation, &[ShipMiningDrone], channel.clone()).await;
-    }
-}
-
-async fn miner_loop(mut ship_automation: ShipAutomation, _channel: mpsc::Sender<Ship>) {
-    loop {
-        miner::mine(&mut ship_automation, false).await;
-    }
-}
-
-async fn explorer_loop(ship_automation: ShipAutomation, channel: mpsc::Sender<Ship>) {
     loop {
-        admin::buy_ship(&ship_automation, &[ShipMiningDrone], &channel).await;
-        sleep(time::Duration::from_secs(120)).await;
-        // explorer::
+        let ship = ship_automation.clone_ship().await.unwrap();
+        let behavior = ship_automation
+            .read()
+            .await
+            .automation_data
+            .behaviors
+            .behavior_for(&ship);
+        trace!("{} runs {}", ship_automation.ship_id, behavior.name());
+        behavior.run(&mut ship_automation, &channel).await;
     }
 }
@@ -0,0 +1,580 @@
+use super::{
+    behavior::ShipBehavior, func::ShipAutomation, ledger::LedgerEntry, planner::CREDIT_RESERVE,
+    seller,
+};
+use spacetraders::{
+    enums::{self, ShipRole, TradeSymbol},
+    requests,
+    responses::schemas::{self, Ship, ShipRequirements},
+    WaypointString,
+};
+
+use async_trait::async_trait;
+use log::{error, info, trace, warn};
+use std::collections::HashMap;
+use tokio::{
+    sync::mpsc,
+    time::{sleep, Duration},
+};
+
+// how long a ship that stays with the outfitter waits for new parts on sale
+const REFIT_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+// What a ship is being fitted out for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Mining,
+    Surveying,
+    Sensing,
+}
+impl Role {
+    // what the ship is registered as, None if nothing is fitted for that
+    pub fn for_ship(ship: &Ship) -> Option<Role> {
+        match ship.registration.role {
+            ShipRole::Excavator => Some(Role::Mining),
+            ShipRole::Surveyor => Some(Role::Surveying),
+            ShipRole::Explorer | ShipRole::Satellite => Some(Role::Sensing),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fitting {
+    Mount(enums::ShipMount),
+    Module(enums::ShipModule),
+}
+impl Fitting {
+    pub fn trade_symbol(&self) -> Option<TradeSymbol> {
+        match self {
+            Fitting::Mount(mount) => mount.trade_symbol(),
+            Fitting::Module(module) => module.trade_symbol(),
+        }
+    }
+}
+
+// A mount or module, what it needs from the ship and how good it is.
+#[derive(Debug, Clone)]
+pub struct Part {
+    pub fitting: Fitting,
+    pub symbol: TradeSymbol,
+    pub requirements: ShipRequirements,
+    // strength of a mount, capacity of a module
+    pub rating: u32,
+}
+impl Part {
+    pub fn from_mount(mount: &schemas::ShipMount) -> Option<Part> {
+        Some(Part {
+            fitting: Fitting::Mount(mount.symbol.clone()),
+            symbol: mount.symbol.trade_symbol()?,
+            requirements: mount.requirements.clone(),
+            rating: mount.strength,
+        })
+    }
+    pub fn from_module(module: &schemas::ShipModule) -> Option<Part> {
+        Some(Part {
+            fitting: Fitting::Module(module.symbol.clone()),
+            symbol: module.symbol.trade_symbol()?,
+            requirements: module.requirements.clone(),
+            rating: module.capacity,
+        })
+    }
+
+    // quarters house the crew every other part needs, taking them off would
+    // leave the ship short of crew, so they stay whatever the role
+    pub fn is_fixed(&self) -> bool {
+        matches!(
+            self.fitting,
+            Fitting::Module(
+                enums::ShipModule::ModuleCrewQuartersI | enums::ShipModule::ModuleEnvoyQuartersI
+            )
+        )
+    }
+
+    // how much the part does for the role, nothing at all for most
+    pub fn value(&self, role: Role) -> f64 {
+        let rating = self.rating.max(1) as f64;
+        match (&self.fitting, role) {
+            (Fitting::Mount(mount), Role::Mining) if mount.extractor().is_some() => rating,
+            (Fitting::Mount(mount), Role::Surveying) if mount.is_surveyor() => rating,
+            (Fitting::Mount(mount), Role::Sensing) if mount.is_sensor_array() => rating,
+            // room for whatever the lasers bring up
+            (Fitting::Module(enums::ShipModule::ModuleCargoHoldI), Role::Mining) => rating / 10.0,
+            _ => 0.0,
+        }
+    }
+}
+
+// what the frame, reactor and crew quarters leave for mounts and modules
+#[derive(Debug, Clone, Copy)]
+struct Capacity {
+    mounting_points: i32,
+    module_slots: i32,
+    power: i32,
+    crew: i32,
+}
+impl Capacity {
+    fn of(ship: &Ship, fitted: &[Part]) -> Self {
+        let base = [
+            &ship.frame.requirements,
+            &ship.reactor.requirements,
+            &ship.engine.requirements,
+        ];
+        let fitted_crew: i32 = fitted.iter().map(|part| part.requirements.crew).sum();
+        Capacity {
+            mounting_points: ship.frame.mounting_points as i32,
+            module_slots: ship.frame.module_slots as i32,
+            power: ship.reactor.power_output as i32
+                - base.iter().map(|needs| needs.power).sum::<i32>(),
+            crew: ship.crew.capacity - (ship.crew.required - fitted_crew),
+        }
+    }
+
+    // false if the part doesn't fit in what's left
+    fn take(&mut self, part: &Part) -> bool {
+        let (points, slots) = match part.fitting {
+            Fitting::Mount(_) => (1, 0),
+            Fitting::Module(_) => (0, part.requirements.slots.max(1)),
+        };
+        if points > self.mounting_points
+            || slots > self.module_slots
+            || part.requirements.power > self.power
+            || part.requirements.crew > self.crew
+        {
+            return false;
+        }
+        self.mounting_points -= points;
+        self.module_slots -= slots;
+        self.power -= part.requirements.power;
+        self.crew -= part.requirements.crew;
+        true
+    }
+}
+
+// A part for sale at a market.
+#[derive(Debug, Clone)]
+pub struct Offer {
+    pub part: Part,
+    pub market: WaypointString,
+    pub price: f64,
+}
+
+// What to buy, install and take off to fit a ship out for a role.
+#[derive(Debug, Clone)]
+pub struct LoadoutPlan {
+    pub role: Role,
+    pub install: Vec<Offer>,
+    pub remove: Vec<Part>,
+    // how much better the ship gets at the role
+    pub gain: f64,
+}
+impl LoadoutPlan {
+    pub fn cost(&self) -> f64 {
+        self.install.iter().map(|offer| offer.price).sum()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.install.is_empty() && self.remove.is_empty()
+    }
+}
+
+// the best loadout for the role from what the ship has and what's for sale,
+// filled greedily with the most valuable parts first
+pub fn plan_loadout(ship: &Ship, role: Role, offers: &[Offer]) -> LoadoutPlan {
+    trace!("Plan Loadout");
+    let current: Vec<Part> = ship
+        .mounts
+        .iter()
+        .filter_map(Part::from_mount)
+        .chain(ship.modules.iter().filter_map(Part::from_module))
+        .collect();
+
+    // what we have first, so only better parts are bought
+    let mut candidates: Vec<(Part, Option<&Offer>)> = current
+        .iter()
+        .map(|part| (part.clone(), None))
+        .chain(offers.iter().map(|offer| (offer.part.clone(), Some(offer))))
+        .filter(|(part, _)| part.value(role) > 0.0)
+        .collect();
+    candidates.sort_by(|(a, a_offer), (b, b_offer)| {
+        let price = |offer: &Option<&Offer>| offer.map_or(0.0, |offer| offer.price);
+        b.value(role)
+            .total_cmp(&a.value(role))
+            .then(price(a_offer).total_cmp(&price(b_offer)))
+    });
+
+    let mut capacity = Capacity::of(ship, &current);
+    let mut kept: Vec<Part> = Vec::new();
+    for part in current.iter().filter(|part| part.is_fixed()) {
+        capacity.take(part);
+        kept.push(part.clone());
+    }
+    let mut install: Vec<Offer> = Vec::new();
+    for (part, offer) in candidates {
+        match offer {
+            None if capacity.take(&part) => kept.push(part),
+            None => {}
+            // a market sells as many as we can fit
+            Some(offer) => {
+                while capacity.take(&part) {
+                    install.push(offer.clone());
+                }
+            }
+        }
+    }
+    // whatever else is fitted stays on while there's room for it
+    for part in current
+        .iter()
+        .filter(|part| part.value(role) <= 0.0 && !part.is_fixed())
+    {
+        if capacity.take(part) {
+            kept.push(part.clone());
+        }
+    }
+
+    let mut remove = current.clone();
+    for part in kept.iter() {
+        if let Some(index) = remove.iter().position(|left| left.fitting == part.fitting) {
+            remove.remove(index);
+        }
+    }
+    let value = |parts: &mut dyn Iterator<Item = &Part>| -> f64 {
+        parts.map(|part| part.value(role)).sum()
+    };
+    let gain = value(&mut kept.iter().chain(install.iter().map(|offer| &offer.part)))
+        - value(&mut current.iter());
+    LoadoutPlan {
+        role,
+        install,
+        remove,
+        gain,
+    }
+}
+
+// every part we know the specs of and that a market in the ship's system sells
+pub async fn market_offers(ship_automation: &ShipAutomation, ship: &Ship) -> Vec<Offer> {
+    trace!("Market Offers");
+    let unlocked = ship_automation.read().await;
+    let data = &unlocked.automation_data;
+
+    // specs only come with ships, ours and the ones for sale
+    let mut parts: HashMap<TradeSymbol, Part> = HashMap::new();
+    let specs = data
+        .store
+        .shipyard_catalog()
+        .into_iter()
+        .flat_map(|shipyard| shipyard.ships)
+        .filter_map(|offer| offer.spec)
+        .map(|spec| (spec.mounts, spec.modules))
+        .chain(
+            data.ships
+                .values()
+                .map(|ship| (ship.mounts.clone(), ship.modules.clone())),
+        );
+    for (mounts, modules) in specs {
+        let found = mounts
+            .iter()
+            .filter_map(Part::from_mount)
+            .chain(modules.iter().filter_map(Part::from_module));
+        for part in found {
+            parts.entry(part.symbol.clone()).or_insert(part);
+        }
+    }
+
+    parts
+        .into_values()
+        .filter_map(|part| {
+            let price = data
+                .store
+                .best_purchase_price(&ship.nav.system_symbol, &part.symbol)?;
+            Some(Offer {
+                part,
+                market: price.waypoint,
+                price: price.purchase_price,
+            })
+        })
+        .collect()
+}
+
+// buys the parts the plan needs, has a shipyard fit them and sells whatever
+// came off, false if the plan couldn't be carried out
+pub async fn refit(ship_automation: &mut ShipAutomation, role: Role) -> bool {
+    trace!("Refit");
+    let ship = ship_automation.clone_ship().await.unwrap();
+    let offers = market_offers(ship_automation, &ship).await;
+    let plan = plan_loadout(&ship, role, &offers);
+    if plan.is_empty() || plan.gain <= 0.0 {
+        info!(
+            "{} is already fitted as well as it can be for {:?}",
+            ship_automation.ship_id, role
+        );
+        return false;
+    }
+    if ship_automation.get_credits().await - plan.cost() < CREDIT_RESERVE {
+        info!(
+            "{} can't afford {} credits of parts yet",
+            ship_automation.ship_id,
+            plan.cost()
+        );
+        return false;
+    }
+    let Some(shipyard) = nearest_shipyard(ship_automation, &ship).await else {
+        warn!(
+            "{} knows no shipyard to be refitted at",
+            ship_automation.ship_id
+        );
+        return false;
+    };
+    info!(
+        "{} refits for {:?}: installing {:?}, removing {:?}",
+        ship_automation.ship_id,
+        role,
+        plan.install
+            .iter()
+            .map(|offer| &offer.part.symbol)
+            .collect::<Vec<_>>(),
+        plan.remove
+            .iter()
+            .map(|part| &part.symbol)
+            .collect::<Vec<_>>()
+    );
+
+    let fitted = fit(ship_automation, &plan, &shipyard).await;
+    // what came off and anything we couldn't fit goes back on the market
+    let symbols: Vec<&TradeSymbol> = plan
+        .remove
+        .iter()
+        .map(|part| &part.symbol)
+        .chain(plan.install.iter().map(|offer| &offer.part.symbol))
+        .collect();
+    let inventory = ship_automation.clone_ship().await.unwrap().cargo.inventory;
+    for item in inventory
+        .iter()
+        .filter(|item| symbols.contains(&&item.symbol))
+    {
+        let ship = ship_automation.clone_ship().await.unwrap();
+        seller::sell_item(ship_automation, &ship, item).await;
+    }
+    fitted
+}
+
+// Takes off what the plan removes, then buys the parts one market at a time
+// and has them installed before buying at the next, so a failure leaves little
+// in the hold.
+async fn fit(
+    ship_automation: &ShipAutomation,
+    plan: &LoadoutPlan,
+    shipyard: &WaypointString,
+) -> bool {
+    if !plan.remove.is_empty() {
+        if !ship_automation.ensure_docked_at(shipyard).await {
+            return false;
+        }
+        for part in plan.remove.iter() {
+            if !modify(ship_automation, &part.fitting, false).await {
+                return false;
+            }
+        }
+    }
+
+    let mut markets: Vec<&WaypointString> = Vec::new();
+    for offer in plan.install.iter() {
+        if !markets.contains(&&offer.market) {
+            markets.push(&offer.market);
+        }
+    }
+    for market in markets {
+        if !ship_automation.ensure_docked_at(market).await {
+            return false;
+        }
+        let mut bought = Vec::new();
+        for offer in plan.install.iter().filter(|offer| offer.market == *market) {
+            if ship_automation
+                .purchase_cargo(&offer.part.symbol, 1)
+                .await
+                .is_none()
+            {
+                break;
+            }
+            bought.push(offer);
+        }
+        if !ship_automation.ensure_docked_at(shipyard).await {
+            return false;
+        }
+        for offer in bought.iter() {
+            if !modify(ship_automation, &offer.part.fitting, true).await {
+                return false;
+            }
+        }
+        if bought.len()
+            < plan
+                .install
+                .iter()
+                .filter(|offer| offer.market == *market)
+                .count()
+        {
+            return false;
+        }
+    }
+    true
+}
+
+async fn nearest_shipyard(ship_automation: &ShipAutomation, ship: &Ship) -> Option<WaypointString> {
+    let shipyards: Vec<WaypointString> = ship_automation
+        .read()
+        .await
+        .automation_data
+        .store
+        .shipyard_catalog()
+        .into_iter()
+        .map(|shipyard| shipyard.waypoint)
+        .filter(|waypoint| waypoint.to_system() == ship.nav.system_symbol)
+        .collect();
+    let here = ship_automation
+        .get_waypoint(&ship.nav.waypoint_symbol)
+        .await;
+    let mut nearest = None;
+    for waypoint in shipyards {
+        let shipyard = ship_automation.get_waypoint(&waypoint).await;
+        let distance = ship_automation.euclidean_distance(here.x, here.y, shipyard.x, shipyard.y);
+        if nearest
+            .as_ref()
+            .is_none_or(|(nearest, _)| distance < *nearest)
+        {
+            nearest = Some((distance, waypoint));
+        }
+    }
+    nearest.map(|(_, waypoint)| waypoint)
+}
+
+// installs or removes the part, the ship has to be docked at a shipyard
+async fn modify(ship_automation: &ShipAutomation, fitting: &Fitting, install: bool) -> bool {
+    trace!("Modify Ship");
+    let ship_id = &ship_automation.ship_id;
+    let Some(symbol) =
+        fitting
+            .trade_symbol()
+            .and_then(|symbol| match serde_json::to_value(symbol) {
+                Ok(serde_json::Value::String(symbol)) => Some(symbol),
+                _ => None,
+            })
+    else {
+        return false;
+    };
+
+    let unlocked = ship_automation.read().await;
+    let st_interface = &unlocked.st_interface;
+    let response = match (fitting, install) {
+        (Fitting::Mount(_), true) => st_interface
+            .install_mount(ship_id, requests::InstallMount { symbol })
+            .await
+            .map(|response| {
+                let data = response.data;
+                (
+                    Some(data.mounts),
+                    None,
+                    data.cargo,
+                    data.transaction,
+                    data.agent,
+                )
+            }),
+        (Fitting::Mount(_), false) => st_interface
+            .remove_mount(ship_id, requests::RemoveMount { symbol })
+            .await
+            .map(|response| {
+                let data = response.data;
+                (
+                    Some(data.mounts),
+                    None,
+                    data.cargo,
+                    data.transaction,
+                    data.agent,
+                )
+            }),
+        (Fitting::Module(_), true) => st_interface
+            .install_module(ship_id, requests::InstallModule { symbol })
+            .await
+            .map(|response| {
+                let data = response.data;
+                (
+                    None,
+                    Some(data.modules),
+                    data.cargo,
+                    data.transaction,
+                    data.agent,
+                )
+            }),
+        (Fitting::Module(_), false) => st_interface
+            .remove_module(ship_id, requests::RemoveModule { symbol })
+            .await
+            .map(|response| {
+                let data = response.data;
+                (
+                    None,
+                    Some(data.modules),
+                    data.cargo,
+                    data.transaction,
+                    data.agent,
+                )
+            }),
+    };
+    drop(unlocked);
+
+    match response {
+        Ok((mounts, modules, cargo, transaction, agent)) => {
+            info!(
+                "{} {} {:?}",
+                ship_id,
+                if install { "installed" } else { "removed" },
+                fitting
+            );
+            if let Some(mounts) = mounts {
+                ship_automation.update_mounts(mounts).await;
+            }
+            if let Some(modules) = modules {
+                ship_automation.update_modules(modules).await;
+            }
+            ship_automation.update_cargo(cargo).await;
+            ship_automation
+                .record_transaction(LedgerEntry::refit(&transaction, agent.credits))
+                .await;
+            true
+        }
+        Err(err) => {
+            error!("{} failed to modify {:?}: {:?}", ship_id, fitting, err);
+            false
+        }
+    }
+}
+
+// Refits the ship for what it is registered as, then hands it back to the
+// behavior it would normally run. Ships given the outfitter by type or role
+// have nothing else to run, so they wait a while before looking again.
+#[derive(Debug)]
+pub struct Outfitter;
+#[async_trait]
+impl ShipBehavior for Outfitter {
+    fn name(&self) -> &'static str {
+        "outfitter"
+    }
+    async fn run(&self, ship_automation: &mut ShipAutomation, _channel: &mpsc::Sender<Ship>) {
+        let ship = ship_automation.clone_ship().await.unwrap();
+        match Role::for_ship(&ship) {
+            Some(role) => {
+                refit(ship_automation, role).await;
+            }
+            None => warn!(
+                "{} has no loadout for {:?}",
+                ship_automation.ship_id, ship.registration.role
+            ),
+        }
+        let outfitting = {
+            let mut unlocked = ship_automation.write().await;
+            let behaviors = &mut unlocked.automation_data.behaviors;
+            behaviors.unassign_ship(&ship_automation.ship_id);
+            behaviors.behavior_for(&ship).name() == self.name()
+        };
+        if outfitting {
+            sleep(REFIT_INTERVAL).await;
+        }
+    }
+}
@@ -1,13 +1,41 @@
-use automation::{ship_handler, Automation};
-use spacetraders::{self, SpaceTraders}; // responses::schemas
+use automation::{
+    behavior::BehaviorRegistry,
+    cache::{self, Cache, Dataset},
+    ledger::{self, Activity, ExportFormat, LedgerFilter},
+    ship_handler,
+    store::Store,
+    Automation,
+};
+use spacetraders::{self, enums::ShipType, SpaceTraders, SystemString}; // responses::schemas
 
+use chrono::{DateTime, Local};
 use clap::Parser;
 use log::trace;
+use serde::de::DeserializeOwned;
 use simple_logger::SimpleLogger;
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+};
 
-async fn start_automation(token: Option<String>, email: Option<String>, _username: Option<String>) {
+async fn start_automation(
+    token: Option<String>,
+    email: Option<String>,
+    _username: Option<String>,
+    data_dir: PathBuf,
+    behavior_assignments: Vec<String>,
+) {
     trace!("Starting automation");
+    let mut behaviors = BehaviorRegistry::default();
+    for assignment in behavior_assignments.iter() {
+        if let Err(err) = behaviors.configure(assignment) {
+            eprintln!("Invalid behavior assignment: {err}");
+            process::exit(1)
+        }
+    }
     let st_interface: SpaceTraders = match token {
         Some(token) => {
             spacetraders::SpaceTraders::new(token, email, spacetraders::SpaceTradersEnv::Live)
@@ -17,23 +45,120 @@ async fn start_automation(token: Option<String>, email: Option<String>, _usernam
 
     let _headquarters = st_interface.agent().await.unwrap().data.headquarters;
     let credits = st_interface.agent().await.unwrap().data.credits;
+    let cache = Cache::new(&st_interface, data_dir).await;
+    let store = Store::open(&cache);
+    if store.system_count() == 0 {
+        store.record_systems(&cache::get_systems(&st_interface, &cache).await);
+    }
     // TODO: this should be ran in the background during startup
-    // let euclidean_distances = automation::cache::build_euclidean_distance(&st_interface).await;
-    // let gate_nodes = automation::cache::get_gate_network(&space_traders, headquarters).await;
+    // let euclidean_distances = automation::cache::build_euclidean_distance(&st_interface, &cache).await;
+    // let gate_nodes = automation::cache::get_gate_network(&space_traders, &cache, headquarters).await;
     // println!("{gate_nodes:?}");
     let automation_data = Automation {
         handles: HashMap::new(),
         ships: HashMap::new(),
         contracts: HashMap::new(),
         surveys: HashMap::new(),
-        waypoints: HashMap::new(),
+        waypoints: cache.load(Dataset::Waypoints).unwrap_or_default(),
+        markets: cache.load(Dataset::Markets).unwrap_or_default(),
+        shipyards: cache.load(Dataset::Shipyards).unwrap_or_default(),
         credits,
+        cooldowns: HashMap::new(),
         euclidean_distances: Vec::new(),
+        cache,
+        store,
+        market_scheduler: Default::default(),
+        reservations: Default::default(),
+        behaviors,
     };
 
     ship_handler(st_interface, automation_data).await
 }
 
+// the store the bot wrote in the data dir, exits if there is none
+fn open_offline(data_dir: &Path) -> Store {
+    Store::open_offline(data_dir).unwrap_or_else(|err| {
+        eprintln!("Failed to open the store: {err}, is --data-dir right?");
+        process::exit(1)
+    })
+}
+
+fn export_ledger(data_dir: PathBuf, out: PathBuf, format: ExportFormat, filter: LedgerFilter) {
+    trace!("Export Ledger");
+    let entries = open_offline(&data_dir).ledger(&filter);
+    let result = if out.as_os_str() == "-" {
+        ledger::export(&entries, format, &mut io::stdout().lock())
+    } else {
+        File::create(&out).and_then(|file| {
+            let mut file = io::BufWriter::new(file);
+            ledger::export(&entries, format, &mut file)?;
+            file.flush()
+        })
+    };
+    match result {
+        Ok(()) => eprintln!("Exported {} transactions", entries.len()),
+        Err(err) => {
+            eprintln!("Failed to export to {}: {err}", out.display());
+            process::exit(1)
+        }
+    }
+}
+
+// most jumps to look for a cheaper shipyard
+const MAX_JUMPS: usize = 3;
+
+// An enum or symbol given on the command line, exits if it isn't one. The
+// api types take almost anything, so `valid` rejects what they fall back to.
+fn parse_arg<T: DeserializeOwned>(what: &str, text: String, valid: fn(&T) -> bool) -> T {
+    match serde_json::from_value(serde_json::Value::String(text.to_uppercase())) {
+        Ok(value) if valid(&value) => value,
+        _ => {
+            eprintln!("Invalid {what}: {text}");
+            process::exit(1)
+        }
+    }
+}
+
+fn print_shipyards(data_dir: PathBuf, ship_type: Option<String>, from: Option<String>) {
+    trace!("Print Shipyards");
+    let store = open_offline(&data_dir);
+    let ship_type: Option<ShipType> = ship_type.map(|ship_type| {
+        parse_arg("ship type", ship_type, |ship_type| {
+            !matches!(ship_type, ShipType::Untagged(_))
+        })
+    });
+
+    match (ship_type, from) {
+        (Some(ship_type), Some(from)) => {
+            let from: SystemString =
+                parse_arg("system symbol", from, |system| system.system != "None");
+            match store.cheapest_shipyard(&ship_type, &from, MAX_JUMPS) {
+                Some((offer, jumps)) => println!(
+                    "{:?} is cheapest at {} for {}, {} jumps away",
+                    ship_type,
+                    offer.waypoint.waypoint,
+                    offer.purchase_price.unwrap_or_default(),
+                    jumps
+                ),
+                None => println!("No known shipyard sells {ship_type:?} near {}", from.system),
+            }
+        }
+        (Some(ship_type), None) => {
+            for offer in store.shipyards_selling(&ship_type) {
+                println!("{}: {:?}", offer.waypoint.waypoint, offer.purchase_price);
+            }
+        }
+        (None, _) => {
+            for shipyard in store.shipyard_catalog() {
+                println!("{}:", shipyard.waypoint.waypoint);
+                for offer in shipyard.ships.iter() {
+                    println!("  {:?}: {:?}", offer.ship_type, offer.purchase_price);
+                }
+            }
+        }
+    }
+}
+
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
@@ -47,6 +172,41 @@ struct Args {
     /// Email to register new agent to
     #[arg(short, long)]
     username: Option<String>,
+    /// Directory to keep cached universe data in
+    #[arg(short, long, default_value = "data")]
+    data_dir: PathBuf,
+    /// Behavior to run for a ship role, ship type or ship symbol
+    /// (e.g. SATELLITE=prober, SHIP_MINING_DRONE=miner, MYAGENT-1=idle)
+    #[arg(short, long)]
+    behavior: Vec<String>,
+    /// Export recorded transactions to this file (- for stdout) and exit
+    #[arg(long)]
+    export: Option<PathBuf>,
+    /// Format of the export, csv or jsonl
+    #[arg(long, default_value = "csv")]
+    format: ExportFormat,
+    /// Only export transactions at or after this time (RFC 3339)
+    #[arg(long)]
+    since: Option<DateTime<Local>>,
+    /// Only export transactions before this time (RFC 3339)
+    #[arg(long)]
+    until: Option<DateTime<Local>>,
+    /// Only export transactions of this ship
+    #[arg(long)]
+    ship: Option<String>,
+    /// Only export transactions of this activity
+    /// (sale, purchase, ship_purchase, refuel, contract or refit)
+    #[arg(long)]
+    activity: Option<Activity>,
+    /// List the ships every known shipyard sells and exit
+    #[arg(long)]
+    shipyards: bool,
+    /// Only list shipyards selling this ship type (e.g. SHIP_MINING_DRONE)
+    #[arg(long)]
+    ship_type: Option<String>,
+    /// With --ship-type, the cheapest shipyard reachable from this system
+    #[arg(long)]
+    from: Option<String>,
 }
 
 #[tokio::main]
@@ -61,5 +221,26 @@ async fn main() {
     trace!("Starting SpaceTraders Automation");
 
     let args = Args::parse();
-    start_automation(args.token, args.email, args.username).await;
+    if let Some(out) = args.export {
+        let filter = LedgerFilter {
+            since: args.since,
+            until: args.until,
+            ship: args.ship,
+            activity: args.activity,
+        };
+        export_ledger(args.data_dir, out, args.format, filter);
+        return;
+    }
+    if args.shipyards {
+        print_shipyards(args.data_dir, args.ship_type, args.from);
+        return;
+    }
+    start_automation(
+        args.token,
+        args.email,
+        args.username,
+        args.data_dir,
+        args.behavior,
+    )
+    .await;
 }
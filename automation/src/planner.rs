@@ -0,0 +1,346 @@
+use super::{
+    behavior::{Miner, ShipBehavior},
+    contractor::MINING_SECONDS_PER_UNIT,
+    func::{ShipAutomation, FUEL_PER_MARKET_UNIT},
+    hauler::Hauler,
+    ledger::{Activity, LedgerEntry, LedgerFilter},
+    store::ShipyardOffer,
+};
+use spacetraders::{
+    enums::{self, ShipType},
+    requests,
+    responses::schemas::{self, Ship},
+    SystemString, WaypointString,
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Local};
+use log::{error, info, trace};
+use std::collections::HashMap;
+use tokio::{
+    sync::mpsc,
+    time::{sleep, Duration},
+};
+
+// never spend the last of these, contracts and trades need credits to run
+pub(crate) const CREDIT_RESERVE: f64 = 20_000.0;
+// a ship has to pay for itself within this many hours to be worth buying
+const MAX_PAYBACK_HOURS: f64 = 6.0;
+// how far back the ledger is looked at to judge the ships we own
+const LEDGER_HOURS: i64 = 6;
+// a ship's first few minutes say little about what it earns
+const MIN_LEDGER_MINUTES: i64 = 30;
+// most jumps to fly for a cheaper shipyard
+const MAX_JUMPS: usize = 3;
+// rough fuel for crossing a system to its gate, per jump
+const JUMP_TRAVEL_COST: f64 = 500.0;
+
+// Buying a ship of a type at a shipyard, and how soon it should pay for itself.
+#[derive(Debug, Clone)]
+pub struct PurchasePlan {
+    pub ship_type: ShipType,
+    pub shipyard: WaypointString,
+    pub price: f64,
+    // fuel to get the buying ship to the shipyard
+    pub travel_cost: f64,
+    pub income_per_hour: f64,
+}
+impl PurchasePlan {
+    pub fn cost(&self) -> f64 {
+        self.price + self.travel_cost
+    }
+    pub fn payback_hours(&self) -> f64 {
+        self.cost() / self.income_per_hour
+    }
+}
+
+// the ship purchase that pays for itself the soonest, each type bought at the
+// cheapest shipyard we know within a few jumps, None if nothing pays back in
+// time or we can't afford it without touching the reserve
+pub async fn plan_purchase(ship_automation: &ShipAutomation, ship: &Ship) -> Option<PurchasePlan> {
+    trace!("Plan Purchase");
+    let system = &ship.nav.system_symbol;
+    let income = ledger_income(ship_automation).await;
+    let credits = ship_automation.get_credits().await;
+    let (offers, fuel_price) = {
+        let unlocked = ship_automation.read().await;
+        let store = &unlocked.automation_data.store;
+        let mut ship_types: Vec<ShipType> = store
+            .shipyard_catalog()
+            .into_iter()
+            .flat_map(|shipyard| shipyard.ships.into_iter())
+            .map(|offer| offer.ship_type)
+            .collect();
+        ship_types.sort_by_key(|ship_type| format!("{ship_type:?}"));
+        ship_types.dedup();
+        let offers: Vec<(ShipyardOffer, usize)> = ship_types
+            .iter()
+            .filter_map(|ship_type| store.cheapest_shipyard(ship_type, system, MAX_JUMPS))
+            .collect();
+        let fuel_price = store
+            .best_purchase_price(system, &enums::TradeSymbol::Fuel)
+            .map_or(0.0, |price| price.purchase_price / FUEL_PER_MARKET_UNIT);
+        (offers, fuel_price)
+    };
+    let here = ship_automation
+        .get_waypoint(&ship.nav.waypoint_symbol)
+        .await;
+
+    let mut plans = Vec::new();
+    for (offer, jumps) in offers {
+        let Some(price) = offer.purchase_price else {
+            continue;
+        };
+        let income_per_hour = match (income.get(&offer.ship_type), &offer.spec) {
+            (Some(income), _) => *income,
+            (None, Some(spec)) => mining_income(ship_automation, system, spec).await,
+            (None, None) => continue,
+        };
+        if income_per_hour <= 0.0 {
+            continue;
+        }
+        let travel_cost = if jumps == 0 {
+            let yard = ship_automation.get_waypoint(&offer.waypoint).await;
+            ship_automation.euclidean_distance(here.x, here.y, yard.x, yard.y) as f64 * fuel_price
+        } else {
+            jumps as f64 * JUMP_TRAVEL_COST
+        };
+        plans.push(PurchasePlan {
+            ship_type: offer.ship_type.clone(),
+            shipyard: offer.waypoint.clone(),
+            price,
+            travel_cost,
+            income_per_hour,
+        });
+    }
+    best_plan(plans, credits)
+}
+
+// the plan that pays back the soonest of those that do in time and leave the reserve
+pub(crate) fn best_plan(plans: Vec<PurchasePlan>, credits: f64) -> Option<PurchasePlan> {
+    plans
+        .into_iter()
+        .filter(|plan| plan.payback_hours() <= MAX_PAYBACK_HOURS)
+        .filter(|plan| credits - plan.cost() >= CREDIT_RESERVE)
+        .min_by(|a, b| a.payback_hours().total_cmp(&b.payback_hours()))
+}
+
+// what every type of ship we own made per hour lately, averaged over the ships
+async fn ledger_income(ship_automation: &ShipAutomation) -> HashMap<ShipType, f64> {
+    trace!("Ledger Income");
+    let now = Local::now();
+    let (entries, ships, behaviors) = {
+        let unlocked = ship_automation.read().await;
+        let data = &unlocked.automation_data;
+        let filter = LedgerFilter::since(now - chrono::Duration::hours(LEDGER_HOURS));
+        let behaviors: HashMap<String, &'static str> = data
+            .ships
+            .values()
+            .map(|ship| {
+                (
+                    ship.symbol.clone(),
+                    data.behaviors.behavior_for(ship).name(),
+                )
+            })
+            .collect();
+        (data.store.ledger(&filter), data.ships.clone(), behaviors)
+    };
+    income_by_type(&entries, &ships, &behaviors, now)
+}
+
+// Averages the ledger per ship type. Haulers sell what the miners dug up, so
+// their sales count for the miners they haul for.
+pub(crate) fn income_by_type(
+    entries: &[LedgerEntry],
+    ships: &HashMap<String, Ship>,
+    behaviors: &HashMap<String, &'static str>,
+    now: DateTime<Local>,
+) -> HashMap<ShipType, f64> {
+    let miners: Vec<&String> = behaviors
+        .iter()
+        .filter(|(_, behavior)| **behavior == Miner.name())
+        .map(|(ship_id, _)| ship_id)
+        .collect();
+
+    // buying ships is an investment, not something the ship spent
+    let mut per_ship = HashMap::new();
+    for entry in entries
+        .iter()
+        .filter(|entry| entry.activity != Activity::ShipPurchase)
+    {
+        let hauled = entry.activity == Activity::Sale
+            && behaviors.get(&entry.ship) == Some(&Hauler.name())
+            && !miners.is_empty();
+        let earners = if hauled {
+            miners.clone()
+        } else {
+            vec![&entry.ship]
+        };
+        for earner in earners.iter() {
+            let (profit, _) = per_ship
+                .entry((*earner).clone())
+                .or_insert((0.0, entry.recorded_at));
+            *profit += entry.total / earners.len() as f64;
+        }
+    }
+
+    let mut per_type: HashMap<ShipType, (f64, usize)> = HashMap::new();
+    for (ship_id, (profit, first)) in per_ship {
+        let Some(ship_type) = ships.get(&ship_id).and_then(Ship::ship_type) else {
+            continue;
+        };
+        let hours = (now - first).num_minutes().max(MIN_LEDGER_MINUTES) as f64 / 60.0;
+        let (total, count) = per_type.entry(ship_type).or_default();
+        *total += profit / hours;
+        *count += 1;
+    }
+    per_type
+        .into_iter()
+        .map(|(ship_type, (total, count))| (ship_type, total / count as f64))
+        .collect()
+}
+
+// for ships we never owned, what their mounts could extract at today's prices
+async fn mining_income(
+    ship_automation: &ShipAutomation,
+    system: &SystemString,
+    offer: &schemas::ShipyardShip,
+) -> f64 {
+    let deposits: Vec<&enums::TradeSymbol> = offer
+        .mounts
+        .iter()
+        .filter(|mount| mount.symbol.extractor().is_some())
+        .flat_map(|mount| mount.deposits.iter())
+        .collect();
+    let unlocked = ship_automation.read().await;
+    let prices: Vec<f64> = deposits
+        .into_iter()
+        .filter_map(|symbol| {
+            unlocked
+                .automation_data
+                .store
+                .best_sell_price(system, symbol)
+        })
+        .map(|price| price.sell_price)
+        .collect();
+    if prices.is_empty() {
+        return 0.0;
+    }
+    let average = prices.iter().sum::<f64>() / prices.len() as f64;
+    average * 3600.0 / MINING_SECONDS_PER_UNIT
+}
+
+// Grows the fleet with whichever ship pays for itself the soonest, flying to
+// the shipyard selling it the cheapest, jumping systems if that is where it is.
+#[derive(Debug)]
+pub struct Purchaser;
+#[async_trait]
+impl ShipBehavior for Purchaser {
+    fn name(&self) -> &'static str {
+        "purchaser"
+    }
+    async fn run(&self, ship_automation: &mut ShipAutomation, channel: &mpsc::Sender<Ship>) {
+        expand_fleet(ship_automation, channel).await;
+        sleep(Duration::from_secs(120)).await;
+    }
+}
+
+// buys the ship that pays for itself the soonest, if any pays back in time
+pub async fn expand_fleet(ship_automation: &ShipAutomation, channel: &mpsc::Sender<Ship>) {
+    trace!("Expand Fleet");
+    let ship = ship_automation.clone_ship().await.unwrap();
+    if let Some(plan) = plan_purchase(ship_automation, &ship).await {
+        info!(
+            "{} plans to buy a {:?} at {} for {}, paying back in {:.1} hours",
+            ship_automation.ship_id,
+            plan.ship_type,
+            plan.shipyard.waypoint,
+            plan.price,
+            plan.payback_hours()
+        );
+        buy(ship_automation, &plan, channel).await;
+    }
+}
+
+async fn buy(ship_automation: &ShipAutomation, plan: &PurchasePlan, channel: &mpsc::Sender<Ship>) {
+    trace!("Buy Ship");
+    let home = ship_automation
+        .clone_ship()
+        .await
+        .unwrap()
+        .nav
+        .system_symbol;
+    let system = plan.shipyard.to_system();
+    if system != home && !ship_automation.jump_to_system(&system, MAX_JUMPS).await {
+        return;
+    }
+    if ship_automation.ensure_docked_at(&plan.shipyard).await {
+        buy_at_shipyard(ship_automation, plan, channel).await;
+    }
+    // the ship has work back home
+    if system != home {
+        ship_automation.jump_to_system(&home, MAX_JUMPS).await;
+    }
+}
+
+async fn buy_at_shipyard(
+    ship_automation: &ShipAutomation,
+    plan: &PurchasePlan,
+    channel: &mpsc::Sender<Ship>,
+) {
+    // prices move, so look again before paying
+    let waypoint = ship_automation.get_waypoint(&plan.shipyard).await;
+    let price = ship_automation
+        .get_shipyard(&waypoint)
+        .await
+        .and_then(|shipyard| {
+            shipyard
+                .ships
+                .into_iter()
+                .find(|offer| offer.r#type == plan.ship_type)
+        })
+        .map(|offer| offer.purchase_price);
+    let affordable = match price {
+        Some(price) => ship_automation.get_credits().await - price >= CREDIT_RESERVE,
+        None => false,
+    };
+    if !affordable {
+        info!(
+            "{} can no longer afford a {:?} at {}",
+            ship_automation.ship_id, plan.ship_type, plan.shipyard.waypoint
+        );
+        return;
+    }
+
+    let response = ship_automation
+        .read()
+        .await
+        .st_interface
+        .purchase_ship(requests::PurchaseShip {
+            ship_type: plan.ship_type.clone(),
+            waypoint_symbol: plan.shipyard.waypoint.clone(),
+        })
+        .await;
+    match response {
+        Ok(response) => {
+            let response = response.data;
+            info!(
+                "{} bought {} for {}",
+                ship_automation.ship_id, response.ship.symbol, response.transaction.price
+            );
+            ship_automation
+                .record_transaction(LedgerEntry::ship_purchase(
+                    &response.ship.symbol,
+                    &plan.ship_type,
+                    &response.transaction,
+                    response.agent.credits,
+                ))
+                .await;
+            channel.send(response.ship).await.unwrap();
+        }
+        Err(err) => error!(
+            "{} failed to buy a {:?}: {:?}",
+            ship_automation.ship_id, plan.ship_type, err
+        ),
+    }
+}
@@ -0,0 +1,182 @@
+use super::{
+    behavior::ShipBehavior,
+    func::ShipAutomation,
+    scheduler::{self, MarketScheduler},
+    store::MarketFreshness,
+};
+use spacetraders::{enums, responses::schemas::Ship, WaypointString};
+
+use async_trait::async_trait;
+use log::{info, trace};
+use tokio::{
+    sync::mpsc,
+    time::{sleep, Duration},
+};
+
+// Parks satellites at marketplaces and shipyards and keeps their data fresh,
+// moving on whenever another one in the system has gone staler and nobody
+// else is parked there.
+#[derive(Debug)]
+pub struct Prober;
+#[async_trait]
+impl ShipBehavior for Prober {
+    fn name(&self) -> &'static str {
+        "prober"
+    }
+    async fn run(&self, ship_automation: &mut ShipAutomation, _channel: &mpsc::Sender<Ship>) {
+        ship_automation.wait_for_arrival().await;
+        let ship = ship_automation.clone_ship().await.unwrap();
+        // whatever is where we're parked costs nothing to refresh
+        let here = ship.nav.waypoint_symbol.clone();
+        refresh(ship_automation, &here).await;
+
+        match next_post(ship_automation, &ship).await {
+            Some(post) if post != here => {
+                info!("{} is moving to {}", ship_automation.ship_id, post.waypoint);
+                if ship_automation.ensure_orbit_at(&post).await {
+                    refresh(ship_automation, &post).await;
+                }
+            }
+            _ => sleep(Duration::from_secs(60)).await,
+        }
+    }
+}
+
+// Keeps the market data fresh with ships that have no other job, waiting
+// whenever every market in the system is fresh enough.
+#[derive(Debug)]
+pub struct MarketRefresher;
+#[async_trait]
+impl ShipBehavior for MarketRefresher {
+    fn name(&self) -> &'static str {
+        "market_refresher"
+    }
+    async fn run(&self, ship_automation: &mut ShipAutomation, _channel: &mpsc::Sender<Ship>) {
+        if !refresh_stalest(ship_automation).await {
+            sleep(Duration::from_secs(60)).await;
+        }
+    }
+}
+
+// sends a ship with nothing better to do to the market we trust the least,
+// false when every market in the system is fresh enough
+pub async fn refresh_stalest(ship_automation: &ShipAutomation) -> bool {
+    trace!("Refresh Stalest");
+    let ship = ship_automation.clone_ship().await.unwrap();
+    let here = ship_automation
+        .get_waypoint(&ship.nav.waypoint_symbol)
+        .await;
+    let target = {
+        let mut unlocked = ship_automation.write().await;
+        let data = &mut unlocked.automation_data;
+        let markets = data.store.market_freshness(&ship.nav.system_symbol);
+        data.market_scheduler
+            .assign(&ship_automation.ship_id, markets, here.x, here.y)
+    };
+    let Some(target) = target else {
+        return false;
+    };
+
+    info!(
+        "{} is refreshing market {}",
+        ship_automation.ship_id, target.waypoint
+    );
+    if ship_automation.ensure_orbit_at(&target).await {
+        refresh(ship_automation, &target).await;
+    }
+    ship_automation
+        .write()
+        .await
+        .automation_data
+        .market_scheduler
+        .release(&ship_automation.ship_id);
+    true
+}
+
+// fetches the market and shipyard at the waypoint if our copies have gone stale
+async fn refresh(ship_automation: &ShipAutomation, waypoint: &WaypointString) {
+    trace!("Refresh Post");
+    let waypoint = ship_automation.get_waypoint(waypoint).await;
+    let has_trait = |symbol: enums::WaypointTrait| {
+        waypoint
+            .traits
+            .iter()
+            .any(|r#trait| r#trait.symbol == symbol)
+    };
+
+    if has_trait(enums::WaypointTrait::Marketplace) {
+        let observed_at = ship_automation
+            .read()
+            .await
+            .automation_data
+            .store
+            .market(&waypoint.symbol)
+            .and_then(|market| market.observed_at);
+        if scheduler::needs_refresh(observed_at) {
+            ship_automation.get_market(&waypoint).await;
+        }
+    }
+    if has_trait(enums::WaypointTrait::Shipyard) {
+        // prices only come with a ship in the shipyard, a remote look doesn't count
+        let observed_at = ship_automation
+            .read()
+            .await
+            .automation_data
+            .store
+            .shipyard(&waypoint.symbol)
+            .and_then(|shipyard| shipyard.observed_at());
+        if scheduler::needs_refresh(observed_at) {
+            ship_automation.get_shipyard(&waypoint).await;
+        }
+    }
+}
+
+// where the probe should be parked next, None if it has no post at all
+async fn next_post(ship_automation: &ShipAutomation, ship: &Ship) -> Option<WaypointString> {
+    trace!("Next Post");
+    let system = &ship.nav.system_symbol;
+    let here = &ship.nav.waypoint_symbol;
+    let posts = || async {
+        let unlocked = ship_automation.read().await;
+        let mut posts = unlocked.automation_data.store.market_freshness(system);
+        posts.extend(unlocked.automation_data.store.shipyard_freshness(system));
+        posts
+    };
+
+    let mut candidates = posts().await;
+    if candidates.is_empty() {
+        // makes sure every marketplace and shipyard in the system is known
+        ship_automation.get_waypoints(system).await;
+        candidates = posts().await;
+    }
+    let waypoint = ship_automation.get_waypoint(here).await;
+    let mut unlocked = ship_automation.write().await;
+    choose_post(
+        &mut unlocked.automation_data.market_scheduler,
+        &ship_automation.ship_id,
+        candidates,
+        here,
+        waypoint.x,
+        waypoint.y,
+    )
+}
+
+// the stalest post nobody else watches, otherwise the post we're parked at
+pub(crate) fn choose_post(
+    scheduler: &mut MarketScheduler,
+    ship_id: &str,
+    candidates: Vec<MarketFreshness>,
+    here: &WaypointString,
+    x: i32,
+    y: i32,
+) -> Option<WaypointString> {
+    let parked = candidates.iter().any(|post| post.waypoint == *here);
+    match scheduler.assign(ship_id, candidates, x, y) {
+        Some(post) => Some(post),
+        None if parked => {
+            scheduler.park(ship_id, here.clone());
+            Some(here.clone())
+        }
+        None => None,
+    }
+}
@@ -0,0 +1,228 @@
+use super::{behavior::ShipBehavior, func::ShipAutomation, hauler, store::PriceObservation};
+use spacetraders::{enums::TradeSymbol, requests, responses::schemas::Ship, SpacetradersError};
+
+use async_trait::async_trait;
+use log::{error, info, trace, warn};
+use std::{collections::HashSet, sync::Mutex};
+use tokio::{
+    sync::mpsc,
+    time::{sleep, Duration},
+};
+
+// every refine turns this many units of ore...
+const REFINE_INPUT: i32 = 30;
+// ...into this many units of metal
+const REFINE_OUTPUT: i32 = 10;
+
+// Buys or collects ore, refines it while that pays more than selling the ore,
+// then delivers or sells the metal.
+#[derive(Debug, Default)]
+pub struct Refinery {
+    // goods a ship's refinery turned out not to handle
+    unsupported: Mutex<HashSet<(String, TradeSymbol)>>,
+}
+impl Refinery {
+    pub const NAME: &'static str = "refinery";
+
+    fn supports(&self, ship_id: &str, raw: &TradeSymbol) -> bool {
+        !self
+            .unsupported
+            .lock()
+            .unwrap()
+            .contains(&(ship_id.to_string(), raw.clone()))
+    }
+}
+#[async_trait]
+impl ShipBehavior for Refinery {
+    fn name(&self) -> &'static str {
+        Refinery::NAME
+    }
+    async fn run(&self, ship_automation: &mut ShipAutomation, _channel: &mpsc::Sender<Ship>) {
+        let ship = ship_automation.clone_ship().await.unwrap();
+
+        // refine whatever pays off first
+        for item in ship.cargo.inventory.iter() {
+            if item.units >= REFINE_INPUT
+                && self.supports(&ship.symbol, &item.symbol)
+                && margin(ship_automation, &ship, &item.symbol).await > 0.0
+            {
+                self.refine(ship_automation, &item.symbol).await;
+                return;
+            }
+        }
+
+        let free = ship.cargo.capacity - ship.cargo.units;
+        if free >= REFINE_INPUT {
+            if let Some(miner) =
+                hauler::next_miner(ship_automation, &ship, TradeSymbol::is_refinable).await
+            {
+                hauler::collect(ship_automation, &miner, TradeSymbol::is_refinable).await;
+                return;
+            }
+            if self.buy_ore(ship_automation, &ship, free).await {
+                return;
+            }
+        }
+
+        if ship.cargo.units > 0 {
+            hauler::unload(ship_automation).await;
+        } else {
+            sleep(Duration::from_secs(60)).await;
+        }
+    }
+}
+impl Refinery {
+    async fn refine(&self, ship_automation: &ShipAutomation, raw: &TradeSymbol) {
+        trace!("Refine");
+        let Some(produce) = raw.refines_into() else {
+            return;
+        };
+        ship_automation.wait_for_cooldown().await;
+        if !ship_automation.ensure_orbit().await {
+            return;
+        }
+
+        let response = ship_automation
+            .read()
+            .await
+            .st_interface
+            .ship_refine(
+                &ship_automation.ship_id,
+                requests::ShipRefine {
+                    produce: produce.clone(),
+                },
+            )
+            .await;
+        match response {
+            Ok(response) => {
+                let response = response.data;
+                info!(
+                    "{} refined {:?} into {:?}",
+                    ship_automation.ship_id, raw, produce
+                );
+                ship_automation.update_cargo(response.cargo).await;
+                ship_automation.update_cooldown(&response.cooldown).await;
+            }
+            Err(SpacetradersError::ShipMissingRefineryError) => {
+                error!(
+                    "{} has no refinery, it will idle instead",
+                    ship_automation.ship_id
+                );
+                ship_automation.set_behavior("idle").await;
+            }
+            Err(SpacetradersError::ShipInvalidRefineryGoodError) => {
+                warn!(
+                    "{} can't refine {:?}, it will be sold instead",
+                    ship_automation.ship_id, raw
+                );
+                self.unsupported
+                    .lock()
+                    .unwrap()
+                    .insert((ship_automation.ship_id.clone(), raw.clone()));
+            }
+            Err(err) => error!(
+                "{} failed to refine {:?}: {:?}",
+                ship_automation.ship_id, produce, err
+            ),
+        }
+    }
+
+    // buys the ore with the best known refining margin, returns false if none pays
+    async fn buy_ore(&self, ship_automation: &ShipAutomation, ship: &Ship, free: i32) -> bool {
+        trace!("Buy Ore");
+        // the cheapest offer of every refinable good sold in the system
+        let mut offers: Vec<PriceObservation> = Vec::new();
+        let prices = ship_automation
+            .read()
+            .await
+            .automation_data
+            .store
+            .system_prices(&ship.nav.system_symbol);
+        for price in prices
+            .into_iter()
+            .filter(|price| price.symbol.is_refinable())
+        {
+            match offers.iter_mut().find(|offer| offer.symbol == price.symbol) {
+                Some(offer) if offer.purchase_price <= price.purchase_price => {}
+                Some(offer) => *offer = price,
+                None => offers.push(price),
+            }
+        }
+
+        let mut best = None;
+        for raw_price in offers {
+            if !self.supports(&ship.symbol, &raw_price.symbol) {
+                continue;
+            }
+            let refined = raw_price.symbol.refines_into().unwrap();
+            let Some(refined_price) = sell_price(ship_automation, ship, &refined).await else {
+                continue;
+            };
+            let margin = refined_price * REFINE_OUTPUT as f64
+                - raw_price.purchase_price * REFINE_INPUT as f64;
+            if margin > 0.0 && best.as_ref().is_none_or(|(best, _)| margin > *best) {
+                best = Some((margin, raw_price));
+            }
+        }
+        let Some((_, raw_price)) = best else {
+            return false;
+        };
+
+        let units = free / REFINE_INPUT * REFINE_INPUT;
+        let credits = ship_automation.get_credits().await;
+        let units = units.min((credits / raw_price.purchase_price) as i32);
+        if units < REFINE_INPUT || !ship_automation.ensure_docked_at(&raw_price.waypoint).await {
+            return false;
+        }
+
+        ship_automation
+            .purchase_cargo_in_chunks(&raw_price.symbol, units, raw_price.trade_volume)
+            .await
+            > 0
+    }
+}
+
+// what the refined good is worth to us: open contracts first, then markets
+async fn sell_price(
+    ship_automation: &ShipAutomation,
+    ship: &Ship,
+    symbol: &TradeSymbol,
+) -> Option<f64> {
+    let unlocked = ship_automation.read().await;
+    let contract_price = unlocked
+        .automation_data
+        .contracts
+        .values()
+        .filter(|contract| contract.accepted && !contract.fulfilled)
+        .flat_map(|contract| {
+            contract
+                .terms
+                .deliver
+                .iter()
+                .filter(|good| good.trade_symbol.as_ref() == Some(symbol))
+                .filter(|good| good.remaining_units() > 0)
+                .map(|good| contract.terms.payment.on_fulfilled as f64 / good.units_required as f64)
+        })
+        .max_by(|a, b| a.total_cmp(b));
+    let market_price = unlocked
+        .automation_data
+        .store
+        .best_sell_price(&ship.nav.system_symbol, symbol)
+        .map(|price| price.sell_price);
+    match (contract_price, market_price) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+// credits gained by refining one batch instead of selling the ore as is
+async fn margin(ship_automation: &ShipAutomation, ship: &Ship, raw: &TradeSymbol) -> f64 {
+    let Some(refined) = raw.refines_into() else {
+        return 0.0;
+    };
+    let Some(refined_price) = sell_price(ship_automation, ship, &refined).await else {
+        return 0.0;
+    };
+    let raw_price = sell_price(ship_automation, ship, raw).await.unwrap_or(0.0);
+    refined_price * REFINE_OUTPUT as f64 - raw_price * REFINE_INPUT as f64
+}
@@ -0,0 +1,176 @@
+use super::func::ShipAutomation;
+use spacetraders::{
+    enums::TradeSymbol,
+    responses::schemas::{Contract, Ship},
+};
+
+use chrono::{DateTime, Local};
+use log::trace;
+use std::collections::HashMap;
+
+// Who goods are reserved for.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Owner {
+    Contract(String),
+    // a trade the ship made, only ever covering its own hold
+    Ship(String),
+}
+
+// Goods the fleet has earmarked for contracts and planned trades, so nobody
+// sells or jettisons them on the way, and which hauler is picking up the
+// reserved goods a ship holds.
+#[derive(Debug, Default)]
+pub struct CargoReservations {
+    reservations: HashMap<Owner, HashMap<TradeSymbol, i32>>,
+    // contract reservations stop counting at the contract's deadline
+    deadlines: HashMap<String, DateTime<Local>>,
+    // the hauler that claimed each ship's reserved goods
+    claims: HashMap<String, String>,
+}
+impl CargoReservations {
+    // replaces whatever the owner had reserved of the good
+    pub fn reserve(&mut self, owner: &Owner, symbol: &TradeSymbol, units: i32) {
+        trace!("Reserve Cargo");
+        let reserved = self.reservations.entry(owner.clone()).or_default();
+        if units > 0 {
+            reserved.insert(symbol.clone(), units);
+        } else {
+            reserved.remove(symbol);
+        }
+        if reserved.is_empty() {
+            self.release(owner);
+        }
+    }
+
+    pub fn release(&mut self, owner: &Owner) {
+        self.reservations.remove(owner);
+        if let Owner::Contract(id) = owner {
+            self.deadlines.remove(id);
+        }
+    }
+
+    // reserves what the contract still needs, or releases it once it is done
+    // or can no longer be done
+    pub fn reserve_contract(&mut self, contract: &Contract) {
+        let owner = Owner::Contract(contract.id.clone());
+        if !contract.accepted
+            || contract.fulfilled
+            || contract.time_to_deadline() <= chrono::Duration::zero()
+        {
+            self.release(&owner);
+            return;
+        }
+        for good in contract.terms.deliver.iter() {
+            if let Some(symbol) = &good.trade_symbol {
+                self.reserve(&owner, symbol, good.remaining_units() as i32);
+            }
+        }
+        if self.reservations.contains_key(&owner) {
+            self.deadlines
+                .insert(contract.id.clone(), contract.terms.deadline);
+        }
+    }
+
+    // drops the reservations of every contract past its deadline
+    pub fn release_expired(&mut self) {
+        let now = Local::now();
+        let expired: Vec<String> = self
+            .deadlines
+            .iter()
+            .filter(|(_, deadline)| **deadline <= now)
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in expired {
+            self.release(&Owner::Contract(id));
+        }
+    }
+
+    // units of the good the owner has reserved
+    pub fn reserved_by(&self, owner: &Owner, symbol: &TradeSymbol) -> i32 {
+        self.reservations
+            .get(owner)
+            .and_then(|reserved| reserved.get(symbol))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    // units of the good reserved for contracts that are still running, any
+    // ship may hold those
+    pub fn contract_reserved(&self, symbol: &TradeSymbol) -> i32 {
+        let now = Local::now();
+        self.reservations
+            .iter()
+            .filter(|(owner, _)| match owner {
+                Owner::Contract(id) => self
+                    .deadlines
+                    .get(id)
+                    .is_none_or(|deadline| *deadline > now),
+                Owner::Ship(_) => false,
+            })
+            .filter_map(|(_, reserved)| reserved.get(symbol))
+            .sum()
+    }
+
+    // How many of the good the ship may sell or jettison. A ship may always
+    // sell what it reserved for its own trades, the contract reservations are
+    // covered by the biggest holders in the fleet first, leaving other ships'
+    // trade goods alone.
+    pub fn spare(&self, ships: &HashMap<String, Ship>, ship_id: &str, symbol: &TradeSymbol) -> i32 {
+        let mut holders: Vec<(&str, i32, i32)> = ships
+            .values()
+            .map(|ship| {
+                let units: i32 = ship
+                    .cargo
+                    .inventory
+                    .iter()
+                    .filter(|item| item.symbol == *symbol)
+                    .map(|item| item.units)
+                    .sum();
+                let owner = Owner::Ship(ship.symbol.clone());
+                let traded = units.min(self.reserved_by(&owner, symbol));
+                (ship.symbol.as_str(), units, units - traded)
+            })
+            .filter(|(_, units, _)| *units > 0)
+            .collect();
+        holders.sort_by(|(a, _, a_free), (b, _, b_free)| b_free.cmp(a_free).then(a.cmp(b)));
+
+        let mut left = self.contract_reserved(symbol);
+        for (holder, units, free) in holders {
+            let kept = free.min(left);
+            if holder == ship_id {
+                return units - kept;
+            }
+            left -= kept;
+        }
+        0
+    }
+
+    // takes the ship's reserved goods for the hauler, false if another hauler has them
+    pub fn claim(&mut self, hauler: &str, holder: &str) -> bool {
+        let claimed = self
+            .claims
+            .entry(holder.to_string())
+            .or_insert_with(|| hauler.to_string());
+        claimed == hauler
+    }
+
+    pub fn claimed_by_other(&self, hauler: &str, holder: &str) -> bool {
+        self.claims
+            .get(holder)
+            .is_some_and(|claimed| claimed != hauler)
+    }
+
+    pub fn release_claims(&mut self, hauler: &str) {
+        self.claims.retain(|_, claimed| claimed != hauler);
+    }
+}
+
+// how many of the good the ship may sell or jettison
+pub async fn spare_units(ship_automation: &ShipAutomation, symbol: &TradeSymbol) -> i32 {
+    trace!("Spare Units");
+    let mut unlocked = ship_automation.write().await;
+    let data = &mut unlocked.automation_data;
+    data.reservations.release_expired();
+    data.reservations
+        .spare(&data.ships, &ship_automation.ship_id, symbol)
+}
@@ -0,0 +1,83 @@
+use super::store::{price_confidence, MarketFreshness, Store};
+use spacetraders::{enums::TradeSymbol, WaypointString};
+
+use chrono::{DateTime, Local};
+use log::trace;
+use std::collections::HashMap;
+
+// markets we trust at least this much are left alone
+const FRESH_ENOUGH: f64 = 0.8;
+
+// Hands out the markets whose prices we trust the least to ships that have
+// nothing better to do, making sure two ships never go to the same market.
+#[derive(Debug, Default)]
+pub struct MarketScheduler {
+    assignments: HashMap<String, WaypointString>,
+}
+impl MarketScheduler {
+    pub fn assign(
+        &mut self,
+        ship_id: &str,
+        markets: Vec<MarketFreshness>,
+        x: i32,
+        y: i32,
+    ) -> Option<WaypointString> {
+        trace!("Assign Market");
+        self.release(ship_id);
+
+        let market = markets
+            .into_iter()
+            .filter(|market| market.confidence() < FRESH_ENOUGH)
+            .filter(|market| {
+                !self
+                    .assignments
+                    .values()
+                    .any(|taken| *taken == market.waypoint)
+            })
+            .map(|market| (priority(&market, x, y), market))
+            .max_by(|(a, _), (b, _)| a.total_cmp(b))?
+            .1;
+
+        self.assignments
+            .insert(ship_id.to_string(), market.waypoint.clone());
+        Some(market.waypoint)
+    }
+
+    // keeps the waypoint to the ship, so no other ship is sent there
+    pub fn park(&mut self, ship_id: &str, waypoint: WaypointString) {
+        self.assignments.insert(ship_id.to_string(), waypoint);
+    }
+
+    pub fn release(&mut self, ship_id: &str) -> Option<WaypointString> {
+        self.assignments.remove(ship_id)
+    }
+
+    pub fn assignment(&self, ship_id: &str) -> Option<&WaypointString> {
+        self.assignments.get(ship_id)
+    }
+}
+
+// true if data observed then should be fetched again
+pub fn needs_refresh(observed_at: Option<DateTime<Local>>) -> bool {
+    observed_at
+        .is_none_or(|observed_at| price_confidence(Local::now() - observed_at) < FRESH_ENOUGH)
+}
+
+// how much the latest price of the good at the market can be trusted, from
+// 1.0 (just seen) to 0.0 (never seen)
+pub fn confidence(store: &Store, waypoint: &WaypointString, symbol: &TradeSymbol) -> f64 {
+    store
+        .latest_price(waypoint, symbol)
+        .map_or(0.0, |price| price.confidence())
+}
+
+// stale and busy markets first, discounted by how far away they are
+fn priority(market: &MarketFreshness, x: i32, y: i32) -> f64 {
+    let distance = (((market.x - x) as f64).powi(2) + ((market.y - y) as f64).powi(2)).sqrt();
+    // a market we never saw could be worth anything, treat it as very valuable
+    let value = match market.observed_at {
+        Some(_) => (1.0 + market.value).ln(),
+        None => 20.0,
+    };
+    (1.0 - market.confidence()) * (1.0 + value) / (1.0 + distance / 100.0)
+}
@@ -0,0 +1,207 @@
+use super::{
+    func::{travel_seconds, ShipAutomation, FUEL_PER_MARKET_UNIT},
+    reservations, scheduler,
+    store::PriceObservation,
+};
+use spacetraders::{
+    enums,
+    responses::schemas::{Ship, ShipCargoItem},
+    WaypointString,
+};
+
+use log::{info, trace};
+
+// every trade volume sold pushes the price down by roughly this much
+const PRICE_DROP_PER_VOLUME: f64 = 0.05;
+// stop selling once the price falls below this share of what we came for
+const PRICE_FLOOR_SHARE: f64 = 0.8;
+// after keeping a good this many times, sell it for whatever the market pays
+const MAX_HELD_BACK: u32 = 3;
+// what a second of a ship's time is worth to us, roughly what a miner makes
+const CREDITS_PER_SECOND: f64 = 1.0;
+
+// A market to sell a good at, and what selling there should earn after fuel
+// and the time it takes to get there.
+#[derive(Debug, Clone)]
+pub struct SaleOption {
+    pub waypoint: WaypointString,
+    // None for markets that list the good but we never saw a price at
+    pub price: Option<PriceObservation>,
+    pub net_revenue: f64,
+}
+
+// what the units fetch at the price, with the price sliding after every volume
+fn expected_revenue(price: &PriceObservation, units: i32) -> f64 {
+    let volume = price.trade_volume.max(1) as i32;
+    let mut revenue = 0.0;
+    let mut unit_price = price.sell_price;
+    let mut left = units;
+    while left > 0 {
+        let chunk = left.min(volume);
+        revenue += unit_price * chunk as f64;
+        unit_price *= 1.0 - PRICE_DROP_PER_VOLUME;
+        left -= chunk;
+    }
+    revenue
+}
+
+// the market in the system that nets the most for the units, or the closest
+// one known to trade the good if we have no prices for it anywhere
+pub async fn best_market(
+    ship_automation: &ShipAutomation,
+    ship: &Ship,
+    symbol: &enums::TradeSymbol,
+    units: i32,
+) -> Option<SaleOption> {
+    trace!("Best Market");
+    let system = &ship.nav.system_symbol;
+    let here = ship_automation
+        .get_waypoint(&ship.nav.waypoint_symbol)
+        .await;
+    let (prices, listed, fuel_price) = {
+        let unlocked = ship_automation.read().await;
+        let store = &unlocked.automation_data.store;
+        let prices: Vec<PriceObservation> = store
+            .latest_prices(symbol)
+            .into_iter()
+            .filter(|price| price.waypoint.to_system() == *system)
+            .collect();
+        let listed: Vec<WaypointString> = store
+            .markets_trading(symbol)
+            .into_iter()
+            .map(|(waypoint, _)| waypoint)
+            .filter(|waypoint| waypoint.to_system() == *system)
+            .collect();
+        let fuel_price = store
+            .best_purchase_price(system, &enums::TradeSymbol::Fuel)
+            .map_or(0.0, |price| price.purchase_price / FUEL_PER_MARKET_UNIT);
+        (prices, listed, fuel_price)
+    };
+
+    let mut best: Option<SaleOption> = None;
+    for price in prices {
+        let market = ship_automation.get_waypoint(&price.waypoint).await;
+        let distance = ship_automation.euclidean_distance(here.x, here.y, market.x, market.y);
+        let costs = distance as f64 * fuel_price
+            + travel_seconds(distance as f64, ship.engine.speed) * CREDITS_PER_SECOND;
+        let net_revenue = expected_revenue(&price, units) - costs;
+        if best
+            .as_ref()
+            .is_none_or(|best| net_revenue > best.net_revenue)
+        {
+            best = Some(SaleOption {
+                waypoint: price.waypoint.clone(),
+                price: Some(price),
+                net_revenue,
+            });
+        }
+    }
+    if best.is_some() {
+        return best;
+    }
+
+    let mut closest = None;
+    for waypoint in listed {
+        let market = ship_automation.get_waypoint(&waypoint).await;
+        let distance = ship_automation.euclidean_distance(here.x, here.y, market.x, market.y);
+        if closest
+            .as_ref()
+            .is_none_or(|(closest, _)| distance < *closest)
+        {
+            closest = Some((distance, waypoint));
+        }
+    }
+    closest.map(|(_, waypoint)| SaleOption {
+        waypoint,
+        price: None,
+        net_revenue: 0.0,
+    })
+}
+
+// Sells at the best market for the item, one trade volume at a time, until
+// the price drops too far. Goods kept too often are sold regardless so they
+// don't clog the hold. Reserved units are never sold. Returns the credits
+// earned, None if no market in the system trades the item at all.
+pub async fn sell_item(
+    ship_automation: &mut ShipAutomation,
+    ship: &Ship,
+    item: &ShipCargoItem,
+) -> Option<f64> {
+    trace!("Sell Item");
+    let units = reservations::spare_units(ship_automation, &item.symbol)
+        .await
+        .min(item.units);
+    if units <= 0 {
+        return Some(0.0);
+    }
+    let option = best_market(ship_automation, ship, &item.symbol, units).await?;
+    if !ship_automation.ensure_docked_at(&option.waypoint).await {
+        return Some(0.0);
+    }
+    let held_back = ship_automation
+        .held_back
+        .get(&item.symbol)
+        .copied()
+        .unwrap_or(0);
+    let floor = match option.price {
+        Some(price) if held_back < MAX_HELD_BACK => price.sell_price * PRICE_FLOOR_SHARE,
+        _ => 0.0,
+    };
+    let confidence = scheduler::confidence(
+        &ship_automation.read().await.automation_data.store,
+        &option.waypoint,
+        &item.symbol,
+    );
+    info!(
+        "{} sells {} {:?} at {}, expecting {:.0} credits with {:.0}% confidence",
+        ship_automation.ship_id,
+        units,
+        item.symbol,
+        option.waypoint.waypoint,
+        option.net_revenue,
+        confidence * 100.0
+    );
+
+    let waypoint = ship_automation.get_waypoint(&option.waypoint).await;
+    let mut earned = 0.0;
+    let mut left = units;
+    while left > 0 {
+        // the price we were shown moves with every sale, so look again
+        ship_automation.get_market(&waypoint).await;
+        let price = ship_automation
+            .read()
+            .await
+            .automation_data
+            .store
+            .latest_price(&waypoint.symbol, &item.symbol);
+        let Some(price) = price else {
+            break;
+        };
+        if price.sell_price < floor {
+            info!(
+                "{} keeps {} {:?}, {} only pays {}",
+                ship_automation.ship_id,
+                left,
+                item.symbol,
+                waypoint.symbol.waypoint,
+                price.sell_price
+            );
+            *ship_automation
+                .held_back
+                .entry(item.symbol.clone())
+                .or_insert(0) += 1;
+            break;
+        }
+
+        let chunk = left.min(price.trade_volume.max(1) as i32);
+        let Some(transaction) = ship_automation.sell_cargo(&item.symbol, chunk).await else {
+            break;
+        };
+        earned += transaction.total_price;
+        left -= chunk;
+    }
+    if left == 0 {
+        ship_automation.held_back.remove(&item.symbol);
+    }
+    Some(earned)
+}
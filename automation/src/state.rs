@@ -0,0 +1,257 @@
+use super::func::ShipAutomation;
+use spacetraders::{enums, responses::schemas, WaypointString};
+
+use chrono::{DateTime, Local};
+use log::{error, info, trace};
+use tokio::time::sleep;
+
+// Where a ship is and what it can do right now, derived from its cached nav.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShipState {
+    Docked(WaypointString),
+    InOrbit(WaypointString),
+    InTransit {
+        destination: WaypointString,
+        arrival: DateTime<Local>,
+    },
+}
+impl ShipState {
+    pub fn from_nav(nav: &schemas::ShipNav) -> Self {
+        match nav.status {
+            enums::ShipNavStatus::Docked => ShipState::Docked(nav.waypoint_symbol.clone()),
+            // ships drop into orbit on their own once they arrive
+            enums::ShipNavStatus::InTransit if nav.route.arrival > Local::now() => {
+                ShipState::InTransit {
+                    destination: nav.waypoint_symbol.clone(),
+                    arrival: nav.route.arrival,
+                }
+            }
+            _ => ShipState::InOrbit(nav.waypoint_symbol.clone()),
+        }
+    }
+
+    // the waypoint the ship is at, or will be at once it arrives
+    pub fn waypoint(&self) -> &WaypointString {
+        match self {
+            ShipState::Docked(waypoint) | ShipState::InOrbit(waypoint) => waypoint,
+            ShipState::InTransit { destination, .. } => destination,
+        }
+    }
+}
+
+impl ShipAutomation {
+    pub async fn state(&self) -> ShipState {
+        ShipState::from_nav(&self.clone_ship().await.unwrap().nav)
+    }
+
+    // time left until the ship can extract, survey, refine, jump or scan again
+    pub async fn cooldown(&self) -> Option<chrono::Duration> {
+        let expiration = *self
+            .read()
+            .await
+            .automation_data
+            .cooldowns
+            .get(&self.ship_id)?;
+        let remaining = expiration - Local::now();
+        (remaining > chrono::Duration::zero()).then_some(remaining)
+    }
+
+    // Every response that carries part of a ship goes through these so the
+    // shared copy of the ship never goes stale.
+
+    pub async fn update_nav(&self, nav: schemas::ShipNav) {
+        trace!("Update Nav");
+        if let Some(ship) = self
+            .write()
+            .await
+            .automation_data
+            .ships
+            .get_mut(&self.ship_id)
+        {
+            ship.nav = nav;
+        }
+    }
+    pub async fn update_fuel(&self, fuel: schemas::ShipFuel) {
+        trace!("Update Fuel");
+        if let Some(ship) = self
+            .write()
+            .await
+            .automation_data
+            .ships
+            .get_mut(&self.ship_id)
+        {
+            ship.fuel = fuel;
+        }
+    }
+    pub async fn update_cargo(&self, cargo: schemas::ShipCargo) {
+        trace!("Update Cargo");
+        if let Some(ship) = self
+            .write()
+            .await
+            .automation_data
+            .ships
+            .get_mut(&self.ship_id)
+        {
+            ship.cargo = cargo;
+        }
+    }
+    pub async fn update_mounts(&self, mounts: Vec<schemas::ShipMount>) {
+        trace!("Update Mounts");
+        if let Some(ship) = self
+            .write()
+            .await
+            .automation_data
+            .ships
+            .get_mut(&self.ship_id)
+        {
+            ship.mounts = mounts;
+        }
+    }
+    pub async fn update_modules(&self, modules: Vec<schemas::ShipModule>) {
+        trace!("Update Modules");
+        if let Some(ship) = self
+            .write()
+            .await
+            .automation_data
+            .ships
+            .get_mut(&self.ship_id)
+        {
+            ship.modules = modules;
+        }
+    }
+    pub async fn update_cooldown(&self, cooldown: &schemas::Cooldown) {
+        trace!("Update Cooldown");
+        let expiration =
+            Local::now() + chrono::Duration::seconds(cooldown.remaining_seconds.into());
+        self.write()
+            .await
+            .automation_data
+            .cooldowns
+            .insert(self.ship_id.clone(), expiration);
+    }
+
+    pub async fn wait_for_arrival(&self) {
+        trace!("Wait For Arrival");
+        if let ShipState::InTransit { arrival, .. } = self.state().await {
+            if let Ok(duration) = (arrival - Local::now()).to_std() {
+                info!(
+                    "{} is going to sleep for {} seconds",
+                    self.ship_id,
+                    duration.as_secs()
+                );
+                sleep(duration).await;
+            }
+        }
+        if let Some(ship) = self
+            .write()
+            .await
+            .automation_data
+            .ships
+            .get_mut(&self.ship_id)
+        {
+            if ship.nav.status == enums::ShipNavStatus::InTransit {
+                ship.nav.status = enums::ShipNavStatus::InOrbit;
+            }
+        }
+    }
+
+    pub async fn wait_for_cooldown(&self) {
+        trace!("Wait For Cooldown");
+        if let Some(Ok(duration)) = self.cooldown().await.map(|cooldown| cooldown.to_std()) {
+            info!(
+                "{} is on cooldown for {} seconds",
+                self.ship_id,
+                duration.as_secs()
+            );
+            sleep(duration).await;
+        }
+    }
+
+    // returns false if the ship could not be put in orbit
+    pub async fn ensure_orbit(&self) -> bool {
+        trace!("Ensure Orbit");
+        self.wait_for_arrival().await;
+        if let ShipState::InOrbit(_) = self.state().await {
+            return true;
+        }
+
+        let response = self
+            .read()
+            .await
+            .st_interface
+            .orbit_ship(&self.ship_id)
+            .await;
+        match response {
+            Ok(response) => {
+                self.update_nav(response.data.nav).await;
+                true
+            }
+            Err(err) => {
+                error!("{} failed to orbit: {:?}", self.ship_id, err);
+                self.refresh_nav().await;
+                false
+            }
+        }
+    }
+
+    // returns false if the ship could not be docked
+    pub async fn ensure_docked(&self) -> bool {
+        trace!("Ensure Docked");
+        self.wait_for_arrival().await;
+        if let ShipState::Docked(_) = self.state().await {
+            return true;
+        }
+
+        let response = self
+            .read()
+            .await
+            .st_interface
+            .dock_ship(&self.ship_id)
+            .await;
+        match response {
+            Ok(response) => {
+                self.update_nav(response.data.nav).await;
+                true
+            }
+            Err(err) => {
+                error!("{} failed to dock: {:?}", self.ship_id, err);
+                self.refresh_nav().await;
+                false
+            }
+        }
+    }
+
+    // travels to the waypoint if the ship isn't already there (or on its way),
+    // false if it didn't make it
+    async fn ensure_at(&self, waypoint: &WaypointString) -> bool {
+        trace!("Ensure At");
+        self.wait_for_arrival().await;
+        if self.state().await.waypoint() != waypoint {
+            self.travel_waypoint(waypoint).await;
+        }
+        self.state().await.waypoint() == waypoint
+    }
+
+    pub async fn ensure_orbit_at(&self, waypoint: &WaypointString) -> bool {
+        trace!("Ensure Orbit At");
+        self.ensure_at(waypoint).await && self.ensure_orbit().await
+    }
+
+    pub async fn ensure_docked_at(&self, waypoint: &WaypointString) -> bool {
+        trace!("Ensure Docked At");
+        self.ensure_at(waypoint).await && self.ensure_docked().await
+    }
+
+    // our copy of the nav was wrong, ask the server where the ship really is
+    pub async fn refresh_nav(&self) {
+        let nav = self
+            .read()
+            .await
+            .st_interface
+            .get_ship_nav(&self.ship_id)
+            .await;
+        if let Ok(nav) = nav {
+            self.update_nav(nav.data).await;
+        }
+    }
+}
@@ -0,0 +1,1351 @@
+use super::{
+    cache::Cache,
+    ledger::{Activity, LedgerEntry, LedgerFilter, ProfitAndLoss},
+};
+use spacetraders::{enums, responses::schemas, SystemString, WaypointString};
+
+use chrono::{DateTime, Duration, Local, Utc};
+use log::{error, info, trace};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+const STORE_FILE: &str = "universe.db";
+
+// Every entry upgrades the database by one version (tracked in PRAGMA user_version).
+// Never edit a migration that has been released - append a new one instead.
+const MIGRATIONS: &[&str] = &[
+    // 1: knowledge base
+    "CREATE TABLE meta (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+    CREATE TABLE systems (
+        symbol TEXT PRIMARY KEY,
+        sector TEXT NOT NULL,
+        type TEXT NOT NULL,
+        x INTEGER NOT NULL,
+        y INTEGER NOT NULL
+    );
+    CREATE TABLE waypoints (
+        symbol TEXT PRIMARY KEY,
+        system TEXT NOT NULL,
+        type TEXT NOT NULL,
+        x INTEGER NOT NULL,
+        y INTEGER NOT NULL,
+        faction TEXT,
+        charted INTEGER NOT NULL
+    );
+    CREATE INDEX waypoints_system ON waypoints (system);
+    CREATE TABLE waypoint_traits (
+        waypoint TEXT NOT NULL,
+        trait TEXT NOT NULL,
+        PRIMARY KEY (waypoint, trait)
+    );
+    CREATE INDEX waypoint_traits_trait ON waypoint_traits (trait);
+    CREATE TABLE markets (
+        waypoint TEXT PRIMARY KEY,
+        system TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    );
+    CREATE TABLE market_goods (
+        waypoint TEXT NOT NULL,
+        symbol TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        PRIMARY KEY (waypoint, symbol, kind)
+    );
+    CREATE INDEX market_goods_symbol ON market_goods (symbol);
+    CREATE TABLE trade_goods (
+        waypoint TEXT NOT NULL,
+        symbol TEXT NOT NULL,
+        trade_volume INTEGER NOT NULL,
+        supply TEXT NOT NULL,
+        purchase_price REAL NOT NULL,
+        sell_price REAL NOT NULL,
+        PRIMARY KEY (waypoint, symbol)
+    );
+    CREATE TABLE shipyards (
+        waypoint TEXT PRIMARY KEY,
+        system TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    );
+    CREATE TABLE shipyard_ships (
+        waypoint TEXT NOT NULL,
+        ship_type TEXT NOT NULL,
+        purchase_price REAL,
+        PRIMARY KEY (waypoint, ship_type)
+    );
+    CREATE INDEX shipyard_ships_type ON shipyard_ships (ship_type);
+    CREATE TABLE jump_gates (
+        waypoint TEXT PRIMARY KEY,
+        jump_range REAL NOT NULL,
+        faction TEXT
+    );
+    CREATE TABLE jump_gate_connections (
+        waypoint TEXT NOT NULL,
+        system TEXT NOT NULL,
+        distance INTEGER NOT NULL,
+        PRIMARY KEY (waypoint, system)
+    );",
+    // 2: market price history
+    "CREATE TABLE market_observations (
+        id INTEGER PRIMARY KEY,
+        waypoint TEXT NOT NULL,
+        system TEXT NOT NULL,
+        symbol TEXT NOT NULL,
+        observed_at TEXT NOT NULL,
+        supply TEXT NOT NULL,
+        trade_volume INTEGER NOT NULL,
+        purchase_price REAL NOT NULL,
+        sell_price REAL NOT NULL
+    );
+    CREATE INDEX market_observations_good
+        ON market_observations (symbol, waypoint, observed_at);",
+    // 3: when a market last had one of our ships present
+    "ALTER TABLE markets ADD COLUMN observed_at TEXT;",
+    // 4: every credit we earned or spent
+    "CREATE TABLE ledger (
+        id INTEGER PRIMARY KEY,
+        recorded_at TEXT NOT NULL,
+        ship TEXT NOT NULL,
+        activity TEXT NOT NULL,
+        good TEXT NOT NULL,
+        units INTEGER NOT NULL,
+        price_per_unit REAL NOT NULL,
+        total REAL NOT NULL,
+        balance REAL NOT NULL
+    );
+    CREATE INDEX ledger_recorded_at ON ledger (recorded_at);",
+    // 5: shipyard catalog
+    "ALTER TABLE shipyard_ships ADD COLUMN observed_at TEXT;
+    ALTER TABLE shipyard_ships ADD COLUMN spec TEXT;
+    CREATE TABLE shipyard_transactions (
+        waypoint TEXT NOT NULL,
+        ship_symbol TEXT NOT NULL,
+        price REAL NOT NULL,
+        timestamp TEXT NOT NULL,
+        PRIMARY KEY (waypoint, ship_symbol, timestamp)
+    );",
+    // 6: gates can't be jumped through until they are built
+    "ALTER TABLE waypoints ADD COLUMN under_construction INTEGER NOT NULL DEFAULT 0;",
+];
+
+// prices lose half of their confidence every PRICE_HALF_LIFE minutes
+const PRICE_HALF_LIFE: f64 = 30.0;
+
+// how much a price seen `age` ago can still be trusted, from 1.0 (just now) to 0.0
+pub fn price_confidence(age: Duration) -> f64 {
+    let minutes = age.num_seconds().max(0) as f64 / 60.0;
+    0.5_f64.powf(minutes / PRICE_HALF_LIFE)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarketGoodKind {
+    Import,
+    Export,
+    Exchange,
+}
+impl MarketGoodKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MarketGoodKind::Import => "IMPORT",
+            MarketGoodKind::Export => "EXPORT",
+            MarketGoodKind::Exchange => "EXCHANGE",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SystemRecord {
+    pub symbol: SystemString,
+    pub sector: String,
+    pub r#type: enums::SystemType,
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct WaypointRecord {
+    pub symbol: WaypointString,
+    pub system_symbol: SystemString,
+    pub r#type: enums::WaypointType,
+    pub x: i32,
+    pub y: i32,
+    pub faction: Option<enums::FactionSymbols>,
+    pub charted: bool,
+    pub under_construction: bool,
+    pub traits: Vec<enums::WaypointTrait>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MarketRecord {
+    pub waypoint: WaypointString,
+    pub imports: Vec<enums::TradeSymbol>,
+    pub exports: Vec<enums::TradeSymbol>,
+    pub exchange: Vec<enums::TradeSymbol>,
+    // only filled in when one of our ships was at the market
+    pub trade_goods: Vec<schemas::GetMarketTradeGood>,
+    pub updated_at: DateTime<Local>,
+    // the last time trade_goods were seen, None if we never had a ship there
+    pub observed_at: Option<DateTime<Local>>,
+}
+
+// how fresh our data for a market (or shipyard) is and how much it is worth watching
+#[derive(Debug, Clone)]
+pub struct MarketFreshness {
+    pub waypoint: WaypointString,
+    pub x: i32,
+    pub y: i32,
+    pub observed_at: Option<DateTime<Local>>,
+    // credits that change hands per trade volume of every good, from the last observation
+    pub value: f64,
+}
+impl MarketFreshness {
+    pub fn age(&self) -> Option<Duration> {
+        self.observed_at
+            .map(|observed_at| Local::now() - observed_at)
+    }
+    pub fn confidence(&self) -> f64 {
+        self.age().map(price_confidence).unwrap_or(0.0)
+    }
+}
+
+// a trade good as it was seen at a market at one point in time
+#[derive(Debug, Clone)]
+pub struct PriceObservation {
+    pub waypoint: WaypointString,
+    pub symbol: enums::TradeSymbol,
+    pub observed_at: DateTime<Local>,
+    pub supply: enums::GetMarketSupplyType,
+    pub trade_volume: u32,
+    pub purchase_price: f64,
+    pub sell_price: f64,
+}
+impl PriceObservation {
+    pub fn age(&self) -> Duration {
+        Local::now() - self.observed_at
+    }
+    pub fn confidence(&self) -> f64 {
+        price_confidence(self.age())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ShipyardRecord {
+    pub waypoint: WaypointString,
+    pub ships: Vec<ShipyardOffer>,
+    pub updated_at: DateTime<Local>,
+}
+impl ShipyardRecord {
+    // the last time one of our ships saw the prices, None if none ever did
+    pub fn observed_at(&self) -> Option<DateTime<Local>> {
+        self.ships
+            .iter()
+            .filter_map(|offer| offer.observed_at)
+            .max()
+    }
+}
+
+// a ship type a shipyard sells, prices and specs are only known when one of
+// our ships was at the shipyard
+#[derive(Debug, Clone)]
+pub struct ShipyardOffer {
+    pub waypoint: WaypointString,
+    pub ship_type: enums::ShipType,
+    pub purchase_price: Option<f64>,
+    pub spec: Option<schemas::ShipyardShip>,
+    pub observed_at: Option<DateTime<Local>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct JumpGateRecord {
+    pub waypoint: WaypointString,
+    pub jump_range: f64,
+    pub faction: Option<enums::FactionSymbols>,
+    pub connections: Vec<(SystemString, i32)>,
+}
+
+// a system found walking the jump gates, and the hop and gate it was reached from
+struct JumpHop {
+    system: SystemString,
+    jumps: usize,
+    via: Option<(usize, WaypointString)>,
+}
+
+// Embedded SQLite knowledge base shared by the bot and anything else that
+// wants to read it. Like the cache it is wiped whenever the server resets,
+// all but the ledger, which keeps our books across resets.
+#[derive(Debug)]
+pub struct Store {
+    path: PathBuf,
+    conn: Mutex<Connection>,
+}
+impl Store {
+    pub fn open(cache: &Cache) -> Self {
+        Store::open_path(cache.path(STORE_FILE), cache.reset_date())
+    }
+
+    pub fn open_path(path: impl Into<PathBuf>, reset_date: DateTime<Local>) -> Self {
+        let path = path.into();
+        let mut conn = connect(&path);
+
+        let stored_reset: Option<String> = conn
+            .query_row(
+                "SELECT value FROM meta WHERE key = 'reset_date'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()
+            .expect("Failed to read store metadata");
+        if stored_reset.is_some_and(|stored| stored != reset_date.to_rfc3339()) {
+            info!("Server reset - clearing {}", path.display());
+            clear_universe(&mut conn).expect("Failed to clear outdated store");
+        }
+        conn.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES ('reset_date', ?1)",
+            params![reset_date.to_rfc3339()],
+        )
+        .expect("Failed to write store metadata");
+
+        Store {
+            path,
+            conn: Mutex::new(conn),
+        }
+    }
+
+    // reads what the bot recorded without asking the server whether it reset,
+    // fails rather than creating an empty store the bot never wrote to
+    pub fn open_offline(data_dir: &Path) -> io::Result<Self> {
+        let path = data_dir.join(STORE_FILE);
+        if !path.is_file() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no store at {}", path.display()),
+            ));
+        }
+        Ok(Store {
+            conn: Mutex::new(connect(&path)),
+            path,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn write<F>(&self, what: &str, func: F)
+    where
+        F: FnOnce(&Transaction) -> rusqlite::Result<()>,
+    {
+        let mut conn = self.conn.lock().unwrap();
+        let result = conn.transaction().and_then(|tx| {
+            func(&tx)?;
+            tx.commit()
+        });
+        if let Err(err) = result {
+            error!("Failed to record {}: {}", what, err);
+        }
+    }
+
+    fn read<T: Default, F>(&self, what: &str, func: F) -> T
+    where
+        F: FnOnce(&Connection) -> rusqlite::Result<T>,
+    {
+        let conn = self.conn.lock().unwrap();
+        func(&conn).unwrap_or_else(|err| {
+            error!("Failed to query {}: {}", what, err);
+            T::default()
+        })
+    }
+
+    // Systems
+
+    pub fn record_systems(&self, systems: &[schemas::System]) {
+        trace!("Record Systems");
+        self.write("systems", |tx| {
+            let mut insert = tx.prepare(
+                "INSERT OR REPLACE INTO systems (symbol, sector, type, x, y)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for system in systems {
+                insert.execute(params![
+                    system.symbol.system,
+                    system.symbol.sector,
+                    to_text(&system.r#type),
+                    system.x,
+                    system.y
+                ])?;
+            }
+            Ok(())
+        })
+    }
+
+    // scans see systems before we ever list them, their waypoints come later
+    pub fn record_scanned_systems(&self, systems: &[schemas::ScannedSystem]) {
+        trace!("Record Scanned Systems");
+        self.write("systems", |tx| {
+            let mut insert = tx.prepare(
+                "INSERT OR REPLACE INTO systems (symbol, sector, type, x, y)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for system in systems {
+                insert.execute(params![
+                    system.symbol.system,
+                    system.symbol.sector,
+                    to_text(&system.r#type),
+                    system.x,
+                    system.y
+                ])?;
+            }
+            Ok(())
+        })
+    }
+
+    pub fn system_count(&self) -> usize {
+        self.read("system count", |conn| {
+            conn.query_row("SELECT COUNT(*) FROM systems", [], |row| row.get(0))
+        })
+    }
+
+    pub fn system(&self, symbol: &SystemString) -> Option<SystemRecord> {
+        self.read("system", |conn| {
+            conn.query_row(
+                "SELECT symbol, sector, type, x, y FROM systems WHERE symbol = ?1",
+                params![symbol.system],
+                system_record,
+            )
+            .optional()
+        })
+    }
+
+    pub fn systems_within(&self, x: i32, y: i32, radius: i32) -> Vec<SystemRecord> {
+        self.read("systems", |conn| {
+            conn.prepare(
+                "SELECT symbol, sector, type, x, y FROM systems
+                 WHERE (x - ?1) * (x - ?1) + (y - ?2) * (y - ?2) <= ?3 * ?3",
+            )?
+            .query_map(params![x, y, radius], system_record)?
+            .collect()
+        })
+    }
+
+    // Waypoints
+
+    pub fn record_waypoints(&self, waypoints: &[schemas::Waypoint]) {
+        trace!("Record Waypoints");
+        self.write("waypoints", |tx| {
+            for waypoint in waypoints {
+                tx.execute(
+                    "INSERT OR REPLACE INTO waypoints (symbol, system, type, x, y, faction, charted,
+                     under_construction) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    params![
+                        waypoint.symbol.waypoint,
+                        waypoint.system_symbol.system,
+                        to_text(&waypoint.r#type),
+                        waypoint.x,
+                        waypoint.y,
+                        waypoint.faction.symbol.as_ref().map(to_text),
+                        !waypoint.chart.submitted_by.is_empty(),
+                        waypoint.is_under_construction
+                    ],
+                )?;
+                tx.execute(
+                    "DELETE FROM waypoint_traits WHERE waypoint = ?1",
+                    params![waypoint.symbol.waypoint],
+                )?;
+                for r#trait in waypoint.traits.iter() {
+                    tx.execute(
+                        "INSERT OR IGNORE INTO waypoint_traits (waypoint, trait) VALUES (?1, ?2)",
+                        params![waypoint.symbol.waypoint, to_text(&r#trait.symbol)],
+                    )?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    pub fn waypoint(&self, symbol: &WaypointString) -> Option<WaypointRecord> {
+        self.read("waypoint", |conn| {
+            conn.query_row(
+                "SELECT symbol, system, type, x, y, faction, charted, under_construction
+                 FROM waypoints
+                 WHERE symbol = ?1",
+                params![symbol.waypoint],
+                waypoint_record,
+            )
+            .optional()?
+            .map(|waypoint| with_traits(conn, waypoint))
+            .transpose()
+        })
+    }
+
+    pub fn waypoints_in_system(&self, system: &SystemString) -> Vec<WaypointRecord> {
+        self.read("waypoints", |conn| {
+            conn.prepare(
+                "SELECT symbol, system, type, x, y, faction, charted, under_construction
+                 FROM waypoints
+                 WHERE system = ?1 ORDER BY symbol",
+            )?
+            .query_map(params![system.system], waypoint_record)?
+            .map(|waypoint| with_traits(conn, waypoint?))
+            .collect()
+        })
+    }
+
+    pub fn waypoints_with_trait(
+        &self,
+        system: &SystemString,
+        r#trait: &enums::WaypointTrait,
+    ) -> Vec<WaypointRecord> {
+        self.read("waypoints", |conn| {
+            conn.prepare(
+                "SELECT w.symbol, w.system, w.type, w.x, w.y, w.faction, w.charted,
+                 w.under_construction
+                 FROM waypoints w JOIN waypoint_traits t ON t.waypoint = w.symbol
+                 WHERE w.system = ?1 AND t.trait = ?2 ORDER BY w.symbol",
+            )?
+            .query_map(params![system.system, to_text(r#trait)], waypoint_record)?
+            .map(|waypoint| with_traits(conn, waypoint?))
+            .collect()
+        })
+    }
+
+    // Markets
+
+    pub fn record_market(&self, waypoint: &schemas::Waypoint, market: &schemas::Market) {
+        trace!("Record Market");
+        self.write("market", |tx| {
+            tx.execute(
+                "INSERT INTO markets (waypoint, system, updated_at) VALUES (?1, ?2, ?3)
+                 ON CONFLICT (waypoint) DO UPDATE SET updated_at = excluded.updated_at",
+                params![
+                    waypoint.symbol.waypoint,
+                    waypoint.system_symbol.system,
+                    Utc::now()
+                ],
+            )?;
+            tx.execute(
+                "DELETE FROM market_goods WHERE waypoint = ?1",
+                params![waypoint.symbol.waypoint],
+            )?;
+            for (kind, goods) in [
+                (MarketGoodKind::Import, &market.imports),
+                (MarketGoodKind::Export, &market.exports),
+                (MarketGoodKind::Exchange, &market.exchange),
+            ] {
+                for good in goods.iter() {
+                    tx.execute(
+                        "INSERT OR IGNORE INTO market_goods (waypoint, symbol, kind)
+                         VALUES (?1, ?2, ?3)",
+                        params![
+                            waypoint.symbol.waypoint,
+                            to_text(&good.symbol),
+                            kind.as_str()
+                        ],
+                    )?;
+                }
+            }
+            // trade goods are only sent while we have a ship there, keep the old ones otherwise
+            if !market.trade_goods.is_empty() {
+                tx.execute(
+                    "DELETE FROM trade_goods WHERE waypoint = ?1",
+                    params![waypoint.symbol.waypoint],
+                )?;
+                let observed_at = Utc::now();
+                tx.execute(
+                    "UPDATE markets SET observed_at = ?2 WHERE waypoint = ?1",
+                    params![waypoint.symbol.waypoint, observed_at],
+                )?;
+                for good in market.trade_goods.iter() {
+                    tx.execute(
+                        "INSERT INTO market_observations (waypoint, system, symbol, observed_at,
+                         supply, trade_volume, purchase_price, sell_price)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                        params![
+                            waypoint.symbol.waypoint,
+                            waypoint.system_symbol.system,
+                            to_text(&good.symbol),
+                            observed_at,
+                            to_text(&good.supply),
+                            good.trade_volume,
+                            good.purchase_price,
+                            good.sell_price
+                        ],
+                    )?;
+                    tx.execute(
+                        "INSERT INTO trade_goods
+                         (waypoint, symbol, trade_volume, supply, purchase_price, sell_price)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                        params![
+                            waypoint.symbol.waypoint,
+                            to_text(&good.symbol),
+                            good.trade_volume,
+                            to_text(&good.supply),
+                            good.purchase_price,
+                            good.sell_price
+                        ],
+                    )?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    pub fn market(&self, waypoint: &WaypointString) -> Option<MarketRecord> {
+        self.read("market", |conn| {
+            let times: Option<(DateTime<Local>, Option<DateTime<Local>>)> = conn
+                .query_row(
+                    "SELECT updated_at, observed_at FROM markets WHERE waypoint = ?1",
+                    params![waypoint.waypoint],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()?;
+            let Some((updated_at, observed_at)) = times else {
+                return Ok(None);
+            };
+
+            let mut market = MarketRecord {
+                waypoint: waypoint.clone(),
+                imports: Vec::new(),
+                exports: Vec::new(),
+                exchange: Vec::new(),
+                trade_goods: Vec::new(),
+                updated_at,
+                observed_at,
+            };
+            let mut goods =
+                conn.prepare("SELECT symbol, kind FROM market_goods WHERE waypoint = ?1")?;
+            let mut rows = goods.query(params![waypoint.waypoint])?;
+            while let Some(row) = rows.next()? {
+                let symbol = from_text(row.get(0)?)?;
+                match row.get::<_, String>(1)?.as_str() {
+                    "IMPORT" => market.imports.push(symbol),
+                    "EXPORT" => market.exports.push(symbol),
+                    _ => market.exchange.push(symbol),
+                }
+            }
+            market.trade_goods = conn
+                .prepare(
+                    "SELECT symbol, trade_volume, supply, purchase_price, sell_price
+                     FROM trade_goods WHERE waypoint = ?1",
+                )?
+                .query_map(params![waypoint.waypoint], |row| {
+                    Ok(schemas::GetMarketTradeGood {
+                        symbol: from_text(row.get(0)?)?,
+                        trade_volume: row.get(1)?,
+                        supply: from_text(row.get(2)?)?,
+                        purchase_price: row.get(3)?,
+                        sell_price: row.get(4)?,
+                    })
+                })?
+                .collect::<rusqlite::Result<_>>()?;
+            Ok(Some(market))
+        })
+    }
+
+    // every market that buys or sells the good, in any system
+    pub fn markets_trading(
+        &self,
+        symbol: &enums::TradeSymbol,
+    ) -> Vec<(WaypointString, MarketGoodKind)> {
+        self.read("markets", |conn| {
+            conn.prepare("SELECT waypoint, kind FROM market_goods WHERE symbol = ?1")?
+                .query_map(params![to_text(symbol)], |row| {
+                    let kind = match row.get::<_, String>(1)?.as_str() {
+                        "IMPORT" => MarketGoodKind::Import,
+                        "EXPORT" => MarketGoodKind::Export,
+                        _ => MarketGoodKind::Exchange,
+                    };
+                    Ok((from_text(row.get(0)?)?, kind))
+                })?
+                .collect()
+        })
+    }
+
+    // every marketplace we know of in the system, including ones we never visited
+    pub fn market_freshness(&self, system: &SystemString) -> Vec<MarketFreshness> {
+        self.read("market freshness", |conn| {
+            conn.prepare(
+                "SELECT w.symbol, w.x, w.y, m.observed_at,
+                 (SELECT COALESCE(SUM(t.trade_volume * (t.purchase_price + t.sell_price)), 0)
+                  FROM trade_goods t WHERE t.waypoint = w.symbol)
+                 FROM waypoints w
+                 JOIN waypoint_traits wt ON wt.waypoint = w.symbol AND wt.trait = ?2
+                 LEFT JOIN markets m ON m.waypoint = w.symbol
+                 WHERE w.system = ?1",
+            )?
+            .query_map(
+                params![system.system, to_text(&enums::WaypointTrait::Marketplace)],
+                |row| {
+                    Ok(MarketFreshness {
+                        waypoint: from_text(row.get(0)?)?,
+                        x: row.get(1)?,
+                        y: row.get(2)?,
+                        observed_at: row.get(3)?,
+                        value: row.get(4)?,
+                    })
+                },
+            )?
+            .collect()
+        })
+    }
+
+    // every shipyard we know of in the system, valued by what its ships cost
+    pub fn shipyard_freshness(&self, system: &SystemString) -> Vec<MarketFreshness> {
+        self.read("shipyard freshness", |conn| {
+            conn.prepare(
+                "SELECT w.symbol, w.x, w.y,
+                 (SELECT MAX(ss.observed_at) FROM shipyard_ships ss WHERE ss.waypoint = w.symbol),
+                 (SELECT COALESCE(SUM(ss.purchase_price), 0)
+                  FROM shipyard_ships ss WHERE ss.waypoint = w.symbol)
+                 FROM waypoints w
+                 JOIN waypoint_traits wt ON wt.waypoint = w.symbol AND wt.trait = ?2
+                 WHERE w.system = ?1",
+            )?
+            .query_map(
+                params![system.system, to_text(&enums::WaypointTrait::Shipyard)],
+                |row| {
+                    Ok(MarketFreshness {
+                        waypoint: from_text(row.get(0)?)?,
+                        x: row.get(1)?,
+                        y: row.get(2)?,
+                        observed_at: row.get(3)?,
+                        value: row.get(4)?,
+                    })
+                },
+            )?
+            .collect()
+        })
+    }
+
+    // Market Prices
+
+    pub fn latest_price(
+        &self,
+        waypoint: &WaypointString,
+        symbol: &enums::TradeSymbol,
+    ) -> Option<PriceObservation> {
+        self.read("latest price", |conn| {
+            conn.query_row(
+                "SELECT waypoint, symbol, observed_at, supply, trade_volume, purchase_price,
+                 sell_price FROM market_observations WHERE waypoint = ?1 AND symbol = ?2
+                 ORDER BY observed_at DESC LIMIT 1",
+                params![waypoint.waypoint, to_text(symbol)],
+                price_observation,
+            )
+            .optional()
+        })
+    }
+
+    // the market turned out not to trade the good after all
+    pub fn forget_good(&self, waypoint: &WaypointString, symbol: &enums::TradeSymbol) {
+        trace!("Forget Good");
+        self.write("forgotten good", |tx| {
+            for table in ["market_goods", "trade_goods", "market_observations"] {
+                tx.execute(
+                    &format!("DELETE FROM {table} WHERE waypoint = ?1 AND symbol = ?2"),
+                    params![waypoint.waypoint, to_text(symbol)],
+                )?;
+            }
+            Ok(())
+        })
+    }
+
+    // the newest observation of the good at every market that has one
+    pub fn latest_prices(&self, symbol: &enums::TradeSymbol) -> Vec<PriceObservation> {
+        self.read("latest prices", |conn| {
+            conn.prepare(
+                "SELECT o.waypoint, o.symbol, o.observed_at, o.supply, o.trade_volume,
+                 o.purchase_price, o.sell_price FROM market_observations o
+                 JOIN (SELECT waypoint, MAX(observed_at) AS observed_at FROM market_observations
+                       WHERE symbol = ?1 GROUP BY waypoint) l
+                 ON o.waypoint = l.waypoint AND o.observed_at = l.observed_at
+                 WHERE o.symbol = ?1",
+            )?
+            .query_map(params![to_text(symbol)], price_observation)?
+            .collect()
+        })
+    }
+
+    // the newest price of every good at every market in the system
+    pub fn system_prices(&self, system: &SystemString) -> Vec<PriceObservation> {
+        self.read("system prices", |conn| {
+            conn.prepare(
+                "SELECT o.waypoint, o.symbol, o.observed_at, o.supply, o.trade_volume,
+                 o.purchase_price, o.sell_price FROM market_observations o
+                 JOIN (SELECT waypoint, symbol, MAX(observed_at) AS observed_at
+                       FROM market_observations WHERE system = ?1 GROUP BY waypoint, symbol) l
+                 ON o.waypoint = l.waypoint AND o.symbol = l.symbol
+                    AND o.observed_at = l.observed_at
+                 WHERE o.system = ?1",
+            )?
+            .query_map(params![system.system], price_observation)?
+            .collect()
+        })
+    }
+
+    // oldest first
+    pub fn price_history(
+        &self,
+        waypoint: &WaypointString,
+        symbol: &enums::TradeSymbol,
+        since: DateTime<Local>,
+    ) -> Vec<PriceObservation> {
+        self.read("price history", |conn| {
+            conn.prepare(
+                "SELECT waypoint, symbol, observed_at, supply, trade_volume, purchase_price,
+                 sell_price FROM market_observations
+                 WHERE waypoint = ?1 AND symbol = ?2 AND observed_at >= ?3
+                 ORDER BY observed_at",
+            )?
+            .query_map(
+                params![
+                    waypoint.waypoint,
+                    to_text(symbol),
+                    since.with_timezone(&Utc)
+                ],
+                price_observation,
+            )?
+            .collect()
+        })
+    }
+
+    // the market in the system paying the most for the good, as far as we know
+    pub fn best_sell_price(
+        &self,
+        system: &SystemString,
+        symbol: &enums::TradeSymbol,
+    ) -> Option<PriceObservation> {
+        self.latest_prices(symbol)
+            .into_iter()
+            .filter(|observation| observation.waypoint.to_system() == *system)
+            .max_by(|a, b| a.sell_price.total_cmp(&b.sell_price))
+    }
+
+    // the market in the system selling the good the cheapest, as far as we know
+    pub fn best_purchase_price(
+        &self,
+        system: &SystemString,
+        symbol: &enums::TradeSymbol,
+    ) -> Option<PriceObservation> {
+        self.latest_prices(symbol)
+            .into_iter()
+            .filter(|observation| observation.waypoint.to_system() == *system)
+            .min_by(|a, b| a.purchase_price.total_cmp(&b.purchase_price))
+    }
+
+    // how old our newest price for the good at this market is
+    pub fn price_staleness(
+        &self,
+        waypoint: &WaypointString,
+        symbol: &enums::TradeSymbol,
+    ) -> Option<Duration> {
+        self.latest_price(waypoint, symbol)
+            .map(|observation| observation.age())
+    }
+
+    // Shipyards
+
+    pub fn record_shipyard(&self, waypoint: &schemas::Waypoint, shipyard: &schemas::Shipyard) {
+        trace!("Record Shipyard");
+        self.write("shipyard", |tx| {
+            tx.execute(
+                "INSERT OR REPLACE INTO shipyards (waypoint, system, updated_at)
+                 VALUES (?1, ?2, ?3)",
+                params![
+                    waypoint.symbol.waypoint,
+                    waypoint.system_symbol.system,
+                    Utc::now()
+                ],
+            )?;
+            // forget the ships it stopped selling
+            let listed: Vec<String> = shipyard
+                .ship_types
+                .iter()
+                .map(|ship_type| to_text(&ship_type.r#type))
+                .collect();
+            let stale: Vec<String> = tx
+                .prepare("SELECT ship_type FROM shipyard_ships WHERE waypoint = ?1")?
+                .query_map(params![waypoint.symbol.waypoint], |row| row.get(0))?
+                .collect::<rusqlite::Result<Vec<String>>>()?
+                .into_iter()
+                .filter(|ship_type| !listed.contains(ship_type))
+                .collect();
+            for ship_type in stale.iter() {
+                tx.execute(
+                    "DELETE FROM shipyard_ships WHERE waypoint = ?1 AND ship_type = ?2",
+                    params![waypoint.symbol.waypoint, ship_type],
+                )?;
+            }
+            for ship_type in listed.iter() {
+                tx.execute(
+                    "INSERT OR IGNORE INTO shipyard_ships (waypoint, ship_type) VALUES (?1, ?2)",
+                    params![waypoint.symbol.waypoint, ship_type],
+                )?;
+            }
+            // prices, specs and transactions are only sent while we have a ship there
+            let observed_at = Utc::now();
+            for ship in shipyard.ships.iter() {
+                tx.execute(
+                    "INSERT INTO shipyard_ships (waypoint, ship_type, purchase_price, observed_at,
+                     spec) VALUES (?1, ?2, ?3, ?4, ?5)
+                     ON CONFLICT (waypoint, ship_type) DO UPDATE SET
+                     purchase_price = excluded.purchase_price,
+                     observed_at = excluded.observed_at, spec = excluded.spec",
+                    params![
+                        waypoint.symbol.waypoint,
+                        to_text(&ship.r#type),
+                        ship.purchase_price,
+                        observed_at,
+                        serde_json::to_string(ship).ok()
+                    ],
+                )?;
+            }
+            for transaction in shipyard.transactions.iter() {
+                tx.execute(
+                    "INSERT OR IGNORE INTO shipyard_transactions
+                     (waypoint, ship_symbol, price, timestamp) VALUES (?1, ?2, ?3, ?4)",
+                    params![
+                        waypoint.symbol.waypoint,
+                        transaction.ship_symbol,
+                        transaction.price,
+                        transaction.timestamp
+                    ],
+                )?;
+            }
+            Ok(())
+        })
+    }
+
+    pub fn shipyard(&self, waypoint: &WaypointString) -> Option<ShipyardRecord> {
+        self.read("shipyard", |conn| {
+            let updated_at: Option<DateTime<Local>> = conn
+                .query_row(
+                    "SELECT updated_at FROM shipyards WHERE waypoint = ?1",
+                    params![waypoint.waypoint],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            let Some(updated_at) = updated_at else {
+                return Ok(None);
+            };
+            let ships = conn
+                .prepare(
+                    "SELECT waypoint, ship_type, purchase_price, spec, observed_at
+                     FROM shipyard_ships WHERE waypoint = ?1",
+                )?
+                .query_map(params![waypoint.waypoint], shipyard_offer)?
+                .collect::<rusqlite::Result<_>>()?;
+            Ok(Some(ShipyardRecord {
+                waypoint: waypoint.clone(),
+                ships,
+                updated_at,
+            }))
+        })
+    }
+
+    // every shipyard selling the ship type, the cheapest known price first
+    pub fn shipyards_selling(&self, ship_type: &enums::ShipType) -> Vec<ShipyardOffer> {
+        self.read("shipyards", |conn| {
+            conn.prepare(
+                "SELECT waypoint, ship_type, purchase_price, spec, observed_at
+                 FROM shipyard_ships WHERE ship_type = ?1
+                 ORDER BY purchase_price IS NULL, purchase_price",
+            )?
+            .query_map(params![to_text(ship_type)], shipyard_offer)?
+            .collect()
+        })
+    }
+
+    // which ships every known shipyard sells, by shipyard
+    pub fn shipyard_catalog(&self) -> Vec<ShipyardRecord> {
+        let waypoints: Vec<WaypointString> = self.read("shipyard catalog", |conn| {
+            conn.prepare("SELECT waypoint FROM shipyards ORDER BY waypoint")?
+                .query_map([], |row| from_text(row.get(0)?))?
+                .collect()
+        });
+        waypoints
+            .iter()
+            .filter_map(|waypoint| self.shipyard(waypoint))
+            .collect()
+    }
+
+    // the cheapest shipyard selling the ship type within a few jumps of the
+    // system, with the number of jumps it takes to get there
+    pub fn cheapest_shipyard(
+        &self,
+        ship_type: &enums::ShipType,
+        from: &SystemString,
+        max_jumps: usize,
+    ) -> Option<(ShipyardOffer, usize)> {
+        let reachable = self.reachable_systems(from, max_jumps);
+        self.shipyards_selling(ship_type)
+            .into_iter()
+            .filter(|offer| offer.purchase_price.is_some())
+            .find_map(|offer| {
+                let system = offer.waypoint.to_system();
+                reachable
+                    .iter()
+                    .find(|(reachable, _)| *reachable == system)
+                    .map(|(_, jumps)| (offer, *jumps))
+            })
+    }
+
+    // oldest first
+    pub fn shipyard_transactions(
+        &self,
+        waypoint: &WaypointString,
+    ) -> Vec<schemas::ShipyardTransaction> {
+        self.read("shipyard transactions", |conn| {
+            conn.prepare(
+                "SELECT waypoint, ship_symbol, price, timestamp FROM shipyard_transactions
+                 WHERE waypoint = ?1 ORDER BY timestamp",
+            )?
+            .query_map(params![waypoint.waypoint], |row| {
+                Ok(schemas::ShipyardTransaction {
+                    waypoint_symbol: row.get(0)?,
+                    ship_symbol: row.get(1)?,
+                    price: row.get(2)?,
+                    timestamp: row.get(3)?,
+                })
+            })?
+            .collect()
+        })
+    }
+
+    // every system reachable through the jump gates we know, nearest first
+    pub fn reachable_systems(
+        &self,
+        from: &SystemString,
+        max_jumps: usize,
+    ) -> Vec<(SystemString, usize)> {
+        self.jump_tree(from, max_jumps)
+            .into_iter()
+            .map(|hop| (hop.system, hop.jumps))
+            .collect()
+    }
+
+    // the gate to jump from and the system it lands in, for every jump to the
+    // system, None if it is further than max_jumps through the gates we know
+    pub fn jump_route(
+        &self,
+        from: &SystemString,
+        to: &SystemString,
+        max_jumps: usize,
+    ) -> Option<Vec<(WaypointString, SystemString)>> {
+        let tree = self.jump_tree(from, max_jumps);
+        let mut index = tree.iter().position(|hop| hop.system == *to)?;
+        let mut route = Vec::new();
+        while let Some((parent, gate)) = &tree[index].via {
+            route.push((gate.clone(), tree[index].system.clone()));
+            index = *parent;
+        }
+        route.reverse();
+        Some(route)
+    }
+
+    // breadth first through the known gates, every system with the way there
+    fn jump_tree(&self, from: &SystemString, max_jumps: usize) -> Vec<JumpHop> {
+        let mut tree = vec![JumpHop {
+            system: from.clone(),
+            jumps: 0,
+            via: None,
+        }];
+        let mut next = 0;
+        while next < tree.len() {
+            let (system, jumps) = (tree[next].system.clone(), tree[next].jumps);
+            let parent = next;
+            next += 1;
+            if jumps >= max_jumps {
+                continue;
+            }
+            let gates = self
+                .waypoints_in_system(&system)
+                .into_iter()
+                .filter(|waypoint| waypoint.r#type == enums::WaypointType::JumpGate)
+                .filter(|waypoint| !waypoint.under_construction);
+            for gate in gates {
+                let Some(gate) = self.jump_gate(&gate.symbol) else {
+                    continue;
+                };
+                for (connected, _) in gate.connections {
+                    if !tree.iter().any(|hop| hop.system == connected) {
+                        tree.push(JumpHop {
+                            system: connected,
+                            jumps: jumps + 1,
+                            via: Some((parent, gate.waypoint.clone())),
+                        });
+                    }
+                }
+            }
+        }
+        tree
+    }
+
+    // Jump Gates
+
+    pub fn record_jump_gate(&self, waypoint: &WaypointString, gate: &schemas::JumpGate) {
+        trace!("Record Jump Gate");
+        self.write("jump gate", |tx| {
+            tx.execute(
+                "INSERT OR REPLACE INTO jump_gates (waypoint, jump_range, faction)
+                 VALUES (?1, ?2, ?3)",
+                params![
+                    waypoint.waypoint,
+                    gate.jump_range,
+                    gate.faction_symbol.as_ref().map(to_text)
+                ],
+            )?;
+            tx.execute(
+                "DELETE FROM jump_gate_connections WHERE waypoint = ?1",
+                params![waypoint.waypoint],
+            )?;
+            for system in gate.connected_systems.iter() {
+                tx.execute(
+                    "INSERT OR REPLACE INTO jump_gate_connections (waypoint, system, distance)
+                     VALUES (?1, ?2, ?3)",
+                    params![waypoint.waypoint, system.symbol.system, system.distance],
+                )?;
+            }
+            Ok(())
+        })
+    }
+
+    pub fn jump_gate(&self, waypoint: &WaypointString) -> Option<JumpGateRecord> {
+        self.read("jump gate", |conn| {
+            let gate: Option<(f64, Option<String>)> = conn
+                .query_row(
+                    "SELECT jump_range, faction FROM jump_gates WHERE waypoint = ?1",
+                    params![waypoint.waypoint],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()?;
+            let Some((jump_range, faction)) = gate else {
+                return Ok(None);
+            };
+            let connections = conn
+                .prepare("SELECT system, distance FROM jump_gate_connections WHERE waypoint = ?1")?
+                .query_map(params![waypoint.waypoint], |row| {
+                    Ok((from_text(row.get(0)?)?, row.get(1)?))
+                })?
+                .collect::<rusqlite::Result<_>>()?;
+            Ok(Some(JumpGateRecord {
+                waypoint: waypoint.clone(),
+                jump_range,
+                faction: faction.map(from_text).transpose()?,
+                connections,
+            }))
+        })
+    }
+
+    // Ledger
+
+    pub fn record_ledger_entry(&self, entry: &LedgerEntry) {
+        trace!("Record Ledger Entry");
+        self.write("ledger entry", |tx| {
+            tx.execute(
+                "INSERT INTO ledger (recorded_at, ship, activity, good, units, price_per_unit,
+                 total, balance) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    entry.recorded_at.with_timezone(&Utc),
+                    entry.ship,
+                    entry.activity.as_str(),
+                    entry.good,
+                    entry.units,
+                    entry.price_per_unit,
+                    entry.total,
+                    entry.balance
+                ],
+            )?;
+            Ok(())
+        })
+    }
+
+    // oldest first
+    pub fn ledger(&self, filter: &LedgerFilter) -> Vec<LedgerEntry> {
+        self.read("ledger", |conn| {
+            conn.prepare(
+                "SELECT recorded_at, ship, activity, good, units, price_per_unit, total, balance
+                 FROM ledger
+                 WHERE (?1 IS NULL OR recorded_at >= ?1) AND (?2 IS NULL OR recorded_at < ?2)
+                   AND (?3 IS NULL OR ship = ?3) AND (?4 IS NULL OR activity = ?4)
+                 ORDER BY recorded_at, id",
+            )?
+            .query_map(
+                params![
+                    filter.since.map(|since| since.with_timezone(&Utc)),
+                    filter.until.map(|until| until.with_timezone(&Utc)),
+                    filter.ship,
+                    filter.activity.map(|activity| activity.as_str())
+                ],
+                ledger_entry,
+            )?
+            .collect()
+        })
+    }
+
+    pub fn profit_by_ship(&self, since: DateTime<Local>) -> HashMap<String, ProfitAndLoss> {
+        self.ledger(&LedgerFilter::since(since)).into_iter().fold(
+            HashMap::new(),
+            |mut ships, entry| {
+                add_to(ships.entry(entry.ship.clone()).or_default(), &entry);
+                ships
+            },
+        )
+    }
+
+    pub fn profit_by_activity(&self, since: DateTime<Local>) -> HashMap<Activity, ProfitAndLoss> {
+        self.ledger(&LedgerFilter::since(since)).into_iter().fold(
+            HashMap::new(),
+            |mut activities, entry| {
+                add_to(activities.entry(entry.activity).or_default(), &entry);
+                activities
+            },
+        )
+    }
+}
+
+fn connect(path: &Path) -> Connection {
+    let mut conn = Connection::open(path).expect("Failed to open store");
+    migrate(&mut conn).expect("Failed to migrate store");
+    conn
+}
+
+// empties every table but the ledger
+fn clear_universe(conn: &mut Connection) -> rusqlite::Result<()> {
+    let tables: Vec<String> = conn
+        .prepare(
+            "SELECT name FROM sqlite_master
+             WHERE type = 'table' AND name NOT IN ('ledger', 'meta')
+               AND name NOT LIKE 'sqlite_%'",
+        )?
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    let tx = conn.transaction()?;
+    for table in tables {
+        tx.execute(&format!("DELETE FROM {table}"), [])?;
+    }
+    tx.commit()
+}
+
+fn migrate(conn: &mut Connection) -> rusqlite::Result<()> {
+    let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        info!("Migrating store to version {}", index + 1);
+        let tx = conn.transaction()?;
+        tx.execute_batch(migration)?;
+        tx.pragma_update(None, "user_version", index + 1)?;
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+// enums and symbols are stored the same way the api sends them
+fn to_text<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(text)) => text,
+        Ok(other) => other.to_string(),
+        Err(_) => String::new(),
+    }
+}
+
+fn from_text<T: DeserializeOwned>(text: String) -> rusqlite::Result<T> {
+    serde_json::from_value(serde_json::Value::String(text))
+        .map_err(|err| rusqlite::Error::ToSqlConversionFailure(Box::new(err)))
+}
+
+fn system_record(row: &rusqlite::Row) -> rusqlite::Result<SystemRecord> {
+    Ok(SystemRecord {
+        symbol: from_text(row.get(0)?)?,
+        sector: row.get(1)?,
+        r#type: from_text(row.get(2)?)?,
+        x: row.get(3)?,
+        y: row.get(4)?,
+    })
+}
+
+fn waypoint_record(row: &rusqlite::Row) -> rusqlite::Result<WaypointRecord> {
+    Ok(WaypointRecord {
+        symbol: from_text(row.get(0)?)?,
+        system_symbol: from_text(row.get(1)?)?,
+        r#type: from_text(row.get(2)?)?,
+        x: row.get(3)?,
+        y: row.get(4)?,
+        faction: row
+            .get::<_, Option<String>>(5)?
+            .map(from_text)
+            .transpose()?,
+        charted: row.get(6)?,
+        under_construction: row.get(7)?,
+        traits: Vec::new(),
+    })
+}
+
+fn price_observation(row: &rusqlite::Row) -> rusqlite::Result<PriceObservation> {
+    Ok(PriceObservation {
+        waypoint: from_text(row.get(0)?)?,
+        symbol: from_text(row.get(1)?)?,
+        observed_at: row.get(2)?,
+        supply: from_text(row.get(3)?)?,
+        trade_volume: row.get(4)?,
+        purchase_price: row.get(5)?,
+        sell_price: row.get(6)?,
+    })
+}
+
+fn shipyard_offer(row: &rusqlite::Row) -> rusqlite::Result<ShipyardOffer> {
+    Ok(ShipyardOffer {
+        waypoint: from_text(row.get(0)?)?,
+        ship_type: from_text(row.get(1)?)?,
+        purchase_price: row.get(2)?,
+        spec: row
+            .get::<_, Option<String>>(3)?
+            .and_then(|spec| serde_json::from_str(&spec).ok()),
+        observed_at: row.get(4)?,
+    })
+}
+
+fn ledger_entry(row: &rusqlite::Row) -> rusqlite::Result<LedgerEntry> {
+    let activity: String = row.get(2)?;
+    Ok(LedgerEntry {
+        recorded_at: row.get(0)?,
+        ship: row.get(1)?,
+        activity: Activity::parse(&activity).ok_or(rusqlite::Error::InvalidColumnType(
+            2,
+            "activity".to_string(),
+            rusqlite::types::Type::Text,
+        ))?,
+        good: row.get(3)?,
+        units: row.get(4)?,
+        price_per_unit: row.get(5)?,
+        total: row.get(6)?,
+        balance: row.get(7)?,
+    })
+}
+
+fn add_to(pnl: &mut ProfitAndLoss, entry: &LedgerEntry) {
+    if entry.total >= 0.0 {
+        pnl.income += entry.total;
+    } else {
+        pnl.spending -= entry.total;
+    }
+    pnl.transactions += 1;
+}
+
+fn with_traits(
+    conn: &Connection,
+    mut waypoint: WaypointRecord,
+) -> rusqlite::Result<WaypointRecord> {
+    waypoint.traits = conn
+        .prepare("SELECT trait FROM waypoint_traits WHERE waypoint = ?1")?
+        .query_map(params![waypoint.symbol.waypoint], |row| {
+            from_text(row.get(0)?)
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(waypoint)
+}
@@ -0,0 +1,206 @@
+use super::{
+    behavior::{self, ShipBehavior},
+    func::ShipAutomation,
+    state::ShipState,
+};
+use spacetraders::{
+    enums,
+    responses::schemas::{self, Ship},
+    WaypointString,
+};
+
+use async_trait::async_trait;
+use chrono::Local;
+use log::{info, trace, warn};
+use std::collections::HashMap;
+use tokio::{
+    sync::mpsc,
+    time::{sleep, Duration},
+};
+
+// how many unexpired surveys we try to keep around for every mining site
+const SURVEYS_PER_SITE: usize = 6;
+
+// Keeps the mining sites our miners work stocked with fresh surveys.
+#[derive(Debug)]
+pub struct Surveyor;
+#[async_trait]
+impl ShipBehavior for Surveyor {
+    fn name(&self) -> &'static str {
+        "surveyor"
+    }
+    async fn run(&self, ship_automation: &mut ShipAutomation, _channel: &mpsc::Sender<Ship>) {
+        let ship = ship_automation.clone_ship().await.unwrap();
+        if !has_surveyor(&ship) {
+            warn!("{} has no surveyor mounted", ship_automation.ship_id);
+            sleep(Duration::from_secs(60)).await;
+            return;
+        }
+
+        let Some(site) = next_site(ship_automation, &ship).await else {
+            sleep(Duration::from_secs(60)).await;
+            return;
+        };
+        if !ship_automation.ensure_orbit_at(&site).await {
+            return;
+        }
+        ship_automation.wait_for_cooldown().await;
+        if let Some(surveys) = ship_automation.create_survey().await {
+            info!(
+                "{} surveyed {} and found {} deposits",
+                ship_automation.ship_id,
+                site.waypoint,
+                surveys.len()
+            );
+        }
+    }
+}
+
+pub fn has_surveyor(ship: &Ship) -> bool {
+    ship.mounts.iter().any(|mount| mount.symbol.is_surveyor())
+}
+
+// the site our miners are working with the fewest surveys left, if any is short
+async fn next_site(ship_automation: &ShipAutomation, surveyor: &Ship) -> Option<WaypointString> {
+    trace!("Next Survey Site");
+    let mut sites: Vec<WaypointString> = {
+        let unlocked = ship_automation.read().await;
+        unlocked
+            .automation_data
+            .ships
+            .values()
+            .filter(|ship| ship.nav.system_symbol == surveyor.nav.system_symbol)
+            .filter(|ship| {
+                unlocked.automation_data.behaviors.behavior_for(ship).name()
+                    == behavior::Miner.name()
+            })
+            .filter_map(|ship| match ShipState::from_nav(&ship.nav) {
+                ShipState::InOrbit(waypoint) => Some(waypoint),
+                _ => None,
+            })
+            .collect()
+    };
+    sites.sort_by(|a, b| a.waypoint.cmp(&b.waypoint));
+    sites.dedup();
+
+    let mut stock = Vec::new();
+    for site in sites {
+        ship_automation.prune_surveys(&site).await;
+        let count = ship_automation
+            .read()
+            .await
+            .automation_data
+            .surveys
+            .get(&site)
+            .map_or(0, |surveys| surveys.len());
+        if count < SURVEYS_PER_SITE {
+            stock.push((count, site != surveyor.nav.waypoint_symbol, site));
+        }
+    }
+    // fewest surveys first, staying put breaks ties
+    stock.sort_by_key(|(count, elsewhere, _)| (*count, *elsewhere));
+    stock.into_iter().next().map(|(_, _, site)| site)
+}
+
+// deposit sizes hold more extractions, and are worth a bit more
+fn size_factor(size: &enums::DepositSize) -> f64 {
+    match size {
+        enums::DepositSize::Small => 1.0,
+        enums::DepositSize::Moderate => 1.25,
+        enums::DepositSize::Large => 1.5,
+    }
+}
+
+impl ShipAutomation {
+    pub async fn prune_surveys(&self, waypoint: &WaypointString) {
+        trace!("Prune Surveys");
+        let now = Local::now();
+        if let Some(surveys) = self.write().await.automation_data.surveys.get_mut(waypoint) {
+            surveys.retain(|survey| survey.expiration > now);
+        }
+    }
+
+    // credits per unit we expect for each good, from open contracts and known markets
+    pub async fn wanted_goods(&self) -> HashMap<enums::TradeSymbol, f64> {
+        trace!("Wanted Goods");
+        let ship = self.clone_ship().await.unwrap();
+        let unlocked = self.read().await;
+        let mut wanted: HashMap<enums::TradeSymbol, f64> = HashMap::new();
+
+        for contract in unlocked.automation_data.contracts.values() {
+            if !contract.accepted || contract.fulfilled {
+                continue;
+            }
+            for good in contract.terms.deliver.iter() {
+                let remaining = good.remaining_units();
+                if let (Some(symbol), true) = (&good.trade_symbol, remaining > 0) {
+                    let per_unit =
+                        contract.terms.payment.on_fulfilled as f64 / good.units_required as f64;
+                    *wanted.entry(symbol.clone()).or_default() += per_unit;
+                }
+            }
+        }
+
+        let surveys = unlocked
+            .automation_data
+            .surveys
+            .get(&ship.nav.waypoint_symbol);
+        for deposit in surveys
+            .into_iter()
+            .flatten()
+            .flat_map(|s| s.deposits.iter())
+        {
+            if let Some(price) = unlocked
+                .automation_data
+                .store
+                .best_sell_price(&ship.nav.system_symbol, &deposit.symbol)
+            {
+                wanted
+                    .entry(deposit.symbol.clone())
+                    .and_modify(|value| *value = value.max(price.sell_price))
+                    .or_insert(price.sell_price);
+            }
+        }
+        wanted
+    }
+
+    // the unexpired survey at the ship's waypoint whose deposits are worth the most
+    pub async fn best_survey(&self) -> Option<schemas::Survey> {
+        trace!("Best Survey");
+        let ship = self.clone_ship().await.unwrap();
+        let waypoint = &ship.nav.waypoint_symbol;
+        self.prune_surveys(waypoint).await;
+
+        let no_surveys = self
+            .read()
+            .await
+            .automation_data
+            .surveys
+            .get(waypoint)
+            .is_none_or(|surveys| surveys.is_empty());
+        if no_surveys && has_surveyor(&ship) {
+            self.create_survey().await;
+            // surveying and extracting share the cooldown
+            self.wait_for_cooldown().await;
+        }
+
+        let wanted = self.wanted_goods().await;
+        let unlocked = self.read().await;
+        unlocked
+            .automation_data
+            .surveys
+            .get(waypoint)?
+            .iter()
+            .map(|survey| {
+                let value = survey
+                    .deposits
+                    .iter()
+                    .map(|deposit| wanted.get(&deposit.symbol).copied().unwrap_or(0.0))
+                    .sum::<f64>()
+                    / survey.deposits.len().max(1) as f64;
+                (value * size_factor(&survey.size), survey)
+            })
+            .max_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, survey)| survey.clone())
+    }
+}
@@ -0,0 +1,70 @@
+use crate::{
+    contractor::{next_step, plan_work, Step, Task},
+    tests::{contract, holding},
+};
+use spacetraders::{enums::TradeSymbol, responses::schemas::ShipCargoItem};
+
+#[test]
+fn offers_are_accepted_before_negotiating_new_ones() {
+    let mut offered = contract("B");
+    offered.accepted = false;
+    let mut fulfilled = contract("C");
+    fulfilled.fulfilled = true;
+
+    assert!(matches!(next_step(&[]), Step::Negotiate));
+    assert!(matches!(next_step(&[fulfilled.clone()]), Step::Negotiate));
+    assert!(matches!(
+        next_step(&[fulfilled, offered.clone()]),
+        Step::Accept
+    ));
+    // one contract at a time
+    assert!(matches!(
+        next_step(&[offered, contract("A")]),
+        Step::Work(contract) if contract.id == "A"
+    ));
+}
+
+#[test]
+fn complete_contracts_are_fulfilled() {
+    let mut complete = contract("A");
+    complete.terms.deliver[0].units_fulfilled = 40;
+
+    assert!(matches!(
+        next_step(&[complete]),
+        Step::Fulfill(contract) if contract.id == "A"
+    ));
+}
+
+#[test]
+fn goods_are_delivered_once_we_hold_what_is_missing() {
+    let contract = contract("A");
+
+    let tasks = plan_work(&holding("SHIP", TradeSymbol::IronOre, 35), &contract);
+    assert!(matches!(tasks[..], [Task::Deliver(_, 30)]));
+
+    let tasks = plan_work(&holding("SHIP", TradeSymbol::IronOre, 10), &contract);
+    assert!(matches!(
+        tasks[..],
+        [Task::Procure(TradeSymbol::IronOre, 20), Task::Wait]
+    ));
+}
+
+#[test]
+fn a_full_hold_delivers_what_it_has_or_makes_room() {
+    let contract = contract("A");
+
+    let mut ship = holding("SHIP", TradeSymbol::CopperOre, 90);
+    ship.cargo.units = 100;
+    ship.cargo.inventory.push(ShipCargoItem {
+        symbol: TradeSymbol::IronOre,
+        name: TradeSymbol::IronOre,
+        units: 10,
+    });
+    assert!(matches!(
+        plan_work(&ship, &contract)[..],
+        [Task::Deliver(_, 10)]
+    ));
+
+    let ship = holding("SHIP", TradeSymbol::CopperOre, 100);
+    assert!(matches!(plan_work(&ship, &contract)[..], [Task::Unload]));
+}
@@ -0,0 +1,23 @@
+use crate::fuel::{fuel_for, need_for};
+
+#[test]
+fn fuel_for_a_hop() {
+    assert_eq!(fuel_for(0), 1);
+    assert_eq!(fuel_for(42), 42);
+}
+
+#[test]
+fn need_covers_the_hop_the_closest_station_and_a_margin() {
+    // a tenth of the tank on top
+    assert_eq!(need_for(400, 50, [80, 30]), 50 + 30 + 40);
+    // no station known past the destination
+    assert_eq!(need_for(400, 50, []), 50 + 40);
+    // refueling at the destination itself
+    assert_eq!(need_for(400, 50, [0, 30]), 50 + 40);
+}
+
+#[test]
+fn need_keeps_a_minimum_margin_and_fits_the_tank() {
+    assert_eq!(need_for(50, 5, []), 5 + 10);
+    assert_eq!(need_for(60, 50, [30]), 60);
+}
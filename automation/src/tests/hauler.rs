@@ -0,0 +1,41 @@
+use crate::{
+    hauler::{plan_deliveries, Delivery},
+    tests::{contract, holding, waypoint},
+};
+use spacetraders::{enums::TradeSymbol, responses::schemas::ShipCargoItem};
+
+fn delivery(contract: &str, units: i32) -> Delivery {
+    Delivery {
+        contract: contract.to_string(),
+        symbol: TradeSymbol::IronOre,
+        destination: waypoint("X1-OE-A1"),
+        units,
+    }
+}
+
+#[test]
+fn contracts_get_what_they_need_and_the_rest_is_sold() {
+    let mut hauler = holding("HAULER", TradeSymbol::IronOre, 50);
+    hauler.cargo.inventory.push(ShipCargoItem {
+        symbol: TradeSymbol::CopperOre,
+        name: TradeSymbol::CopperOre,
+        units: 10,
+    });
+    let mut offered = contract("C");
+    offered.accepted = false;
+
+    assert_eq!(
+        plan_deliveries(&hauler, &[offered, contract("A"), contract("B")]),
+        vec![delivery("A", 30), delivery("B", 20)]
+    );
+}
+
+#[test]
+fn nothing_is_delivered_to_a_fulfilled_contract() {
+    let hauler = holding("HAULER", TradeSymbol::IronOre, 50);
+    let mut fulfilled = contract("A");
+    fulfilled.fulfilled = true;
+
+    assert!(plan_deliveries(&hauler, &[fulfilled]).is_empty());
+    assert!(plan_deliveries(&hauler, &[]).is_empty());
+}
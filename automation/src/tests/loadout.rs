@@ -0,0 +1,121 @@
+use crate::{
+    loadout::{plan_loadout, Offer, Part, Role},
+    tests::{ship, waypoint},
+};
+use spacetraders::{
+    enums::{self, TradeSymbol},
+    responses::schemas::{ShipModule, ShipMount, ShipRequirements},
+};
+
+fn laser(strength: u32, power: i32, crew: i32) -> ShipMount {
+    ShipMount {
+        symbol: enums::ShipMount::MountMiningLaserI,
+        name: String::new(),
+        strength,
+        deposits: Vec::new(),
+        requirements: ShipRequirements {
+            power,
+            crew,
+            slots: 0,
+        },
+    }
+}
+
+fn module(symbol: enums::ShipModule, capacity: u32) -> ShipModule {
+    ShipModule {
+        symbol,
+        capacity,
+        range: 0,
+        name: String::new(),
+        requirements: ShipRequirements {
+            power: 1,
+            crew: 0,
+            slots: 1,
+        },
+    }
+}
+
+fn offer(part: Option<Part>, price: f64) -> Offer {
+    Offer {
+        part: part.unwrap(),
+        market: waypoint("X1-OE-A2"),
+        price,
+    }
+}
+
+fn installed(offers: &[Offer], symbol: TradeSymbol) -> usize {
+    offers
+        .iter()
+        .filter(|offer| offer.part.symbol == symbol)
+        .count()
+}
+
+#[test]
+fn fills_mounting_points_and_module_slots() {
+    let offers = [
+        offer(Part::from_mount(&laser(10, 1, 0)), 100.0),
+        offer(
+            Part::from_module(&module(enums::ShipModule::ModuleCargoHoldI, 30)),
+            50.0,
+        ),
+    ];
+    let plan = plan_loadout(&ship(), Role::Mining, &offers);
+    assert_eq!(installed(&plan.install, TradeSymbol::MountMiningLaserI), 2);
+    assert_eq!(installed(&plan.install, TradeSymbol::ModuleCargoHoldI), 2);
+    assert!(plan.remove.is_empty());
+    assert_eq!(plan.cost(), 300.0);
+    assert_eq!(plan.gain, 26.0);
+}
+
+#[test]
+fn stops_at_the_reactor_power() {
+    let mut ship = ship();
+    ship.frame.mounting_points = 4;
+    let offers = [offer(Part::from_mount(&laser(10, 3, 0)), 100.0)];
+    let plan = plan_loadout(&ship, Role::Mining, &offers);
+    // 8 power to spare buys two 3 power lasers
+    assert_eq!(installed(&plan.install, TradeSymbol::MountMiningLaserI), 2);
+}
+
+#[test]
+fn stops_at_the_crew_and_keeps_the_quarters() {
+    let mut ship = ship();
+    ship.frame.mounting_points = 4;
+    ship.modules
+        .push(module(enums::ShipModule::ModuleCrewQuartersI, 2));
+    let offers = [
+        offer(Part::from_mount(&laser(10, 1, 1)), 100.0),
+        offer(
+            Part::from_module(&module(enums::ShipModule::ModuleCargoHoldI, 30)),
+            50.0,
+        ),
+    ];
+    let plan = plan_loadout(&ship, Role::Mining, &offers);
+    // the quarters stay on, so there are still 2 crew for lasers and a slot left
+    assert!(plan.remove.is_empty());
+    assert_eq!(installed(&plan.install, TradeSymbol::MountMiningLaserI), 2);
+    assert_eq!(installed(&plan.install, TradeSymbol::ModuleCargoHoldI), 1);
+}
+
+#[test]
+fn swaps_a_weaker_part() {
+    let mut ship = ship();
+    ship.frame.mounting_points = 1;
+    ship.mounts.push(laser(5, 1, 0));
+    let offers = [offer(Part::from_mount(&laser(10, 1, 0)), 100.0)];
+    let plan = plan_loadout(&ship, Role::Mining, &offers);
+    assert_eq!(installed(&plan.install, TradeSymbol::MountMiningLaserI), 1);
+    assert_eq!(plan.remove.len(), 1);
+    assert_eq!(plan.remove[0].rating, 5);
+    assert_eq!(plan.gain, 5.0);
+}
+
+#[test]
+fn leaves_a_ship_without_better_parts_alone() {
+    let mut ship = ship();
+    ship.mounts.push(laser(10, 1, 0));
+    ship.mounts.push(laser(10, 1, 0));
+    let offers = [offer(Part::from_mount(&laser(5, 1, 0)), 100.0)];
+    let plan = plan_loadout(&ship, Role::Mining, &offers);
+    assert!(plan.is_empty());
+}
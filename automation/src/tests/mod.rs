@@ -0,0 +1,97 @@
+use spacetraders::{
+    enums::TradeSymbol,
+    responses::schemas::{Contract, Ship, ShipCargoItem, Waypoint},
+    WaypointString,
+};
+
+// a drone in orbit with an empty frame: 2 mounting points, 2 module slots,
+// 8 power and 2 crew to spare
+#[allow(dead_code)]
+fn ship() -> Ship {
+    serde_json::from_str(
+        r#"{"symbol":"AGENT-1",
+            "registration":{"name":"AGENT-1","factionSymbol":"COSMIC","role":"EXCAVATOR"},
+            "nav":{"systemSymbol":"X1-OE","waypointSymbol":"X1-OE-A1",
+                "route":{
+                    "destination":{"symbol":"X1-OE-A1","type":"PLANET","systemSymbol":"X1-OE","x":0,"y":0},
+                    "departure":{"symbol":"X1-OE-A1","type":"PLANET","systemSymbol":"X1-OE","x":0,"y":0},
+                    "departureTime":"2099-01-01T00:00:00.000Z","arrival":"2099-01-01T00:00:00.000Z"},
+                "status":"IN_ORBIT","flightMode":"CRUISE"},
+            "crew":{"current":2,"required":0,"capacity":2,"rotation":"STRICT","morale":100,"wages":0},
+            "frame":{"symbol":"FRAME_DRONE","name":"Drone","moduleSlots":2,"mountingPoints":2,
+                "fuelCapacity":100,"requirements":{"power":1}},
+            "reactor":{"symbol":"REACTOR_CHEMICAL_I","name":"Reactor","powerOutput":10,
+                "requirements":{}},
+            "engine":{"symbol":"ENGINE_IMPULSE_DRIVE_I","name":"Engine","speed":10,
+                "requirements":{"power":1}},
+            "modules":[],"mounts":[],
+            "cargo":{"capacity":0,"units":0,"inventory":[]},
+            "fuel":{"current":100,"capacity":100}}"#,
+    )
+    .unwrap()
+}
+
+#[allow(dead_code)]
+fn waypoint(symbol: &str) -> WaypointString {
+    serde_json::from_value(serde_json::Value::String(symbol.to_string())).unwrap()
+}
+
+// a charted waypoint in X1-OE
+#[allow(dead_code)]
+fn waypoint_at(symbol: &str, x: i32, y: i32) -> Waypoint {
+    serde_json::from_str(&format!(
+        r#"{{"systemSymbol":"X1-OE","symbol":"{symbol}","type":"PLANET","x":{x},"y":{y},
+            "orbitals":[],"traits":[]}}"#
+    ))
+    .unwrap()
+}
+
+// the ship with the units of the good in its hold
+#[allow(dead_code)]
+fn holding(symbol: &str, good: TradeSymbol, units: i32) -> Ship {
+    let mut ship = ship();
+    ship.symbol = symbol.to_string();
+    ship.cargo.capacity = 100;
+    ship.cargo.units = units;
+    ship.cargo.inventory.push(ShipCargoItem {
+        symbol: good.clone(),
+        name: good,
+        units,
+    });
+    ship
+}
+
+// an accepted contract that still needs 30 iron ore
+#[allow(dead_code)]
+fn contract(id: &str) -> Contract {
+    serde_json::from_str(&format!(
+        r#"{{"id":"{id}","factionSymbol":"COSMIC","type":"PROCUREMENT",
+            "terms":{{"deadline":"2099-01-01T00:00:00.000Z",
+                "payment":{{"onAccepted":100,"onFulfilled":1000}},
+                "deliver":[{{"tradeSymbol":"IRON_ORE","destinationSymbol":"X1-OE-A1",
+                    "unitsRequired":40,"unitsFulfilled":10}}]}},
+            "accepted":true,"fulfilled":false,
+            "expiration":"2099-01-01T00:00:00.000Z",
+            "deadlineToAccept":"2099-01-01T00:00:00.000Z"}}"#
+    ))
+    .unwrap()
+}
+
+#[cfg(test)]
+mod contractor;
+#[cfg(test)]
+mod fuel;
+#[cfg(test)]
+mod hauler;
+#[cfg(test)]
+mod loadout;
+#[cfg(test)]
+mod planner;
+#[cfg(test)]
+mod prober;
+#[cfg(test)]
+mod reservations;
+#[cfg(test)]
+mod store;
+#[cfg(test)]
+mod trader;
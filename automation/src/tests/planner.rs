@@ -0,0 +1,85 @@
+use crate::{
+    behavior::{Miner, ShipBehavior},
+    hauler::Hauler,
+    ledger::{Activity, LedgerEntry},
+    planner::{best_plan, income_by_type, PurchasePlan, CREDIT_RESERVE},
+    tests::{ship, waypoint},
+};
+use spacetraders::{
+    enums::{ShipFrame, ShipType},
+    responses::schemas::Ship,
+};
+
+use chrono::{Duration, Local};
+use std::collections::HashMap;
+
+fn entry(ship: &str, activity: Activity, total: f64) -> LedgerEntry {
+    LedgerEntry {
+        recorded_at: Local::now() - Duration::hours(2),
+        ship: ship.to_string(),
+        activity,
+        good: "IRON_ORE".to_string(),
+        units: 1,
+        price_per_unit: total.abs(),
+        total,
+        balance: 0.0,
+    }
+}
+
+fn plan(ship_type: ShipType, price: f64, income_per_hour: f64) -> PurchasePlan {
+    PurchasePlan {
+        ship_type,
+        shipyard: waypoint("X1-OE-A1"),
+        price,
+        travel_cost: 0.0,
+        income_per_hour,
+    }
+}
+
+#[test]
+fn hauled_sales_are_credited_to_the_miners() {
+    let mut ships: HashMap<String, Ship> = HashMap::new();
+    let mut behaviors = HashMap::new();
+    for (symbol, behavior) in [
+        ("MINER-1", Miner.name()),
+        ("MINER-2", Miner.name()),
+        ("HAULER", Hauler.name()),
+    ] {
+        let mut ship = ship();
+        ship.symbol = symbol.to_string();
+        if behavior == Hauler.name() {
+            ship.frame.symbol = ShipFrame::FrameLightFreighter;
+        }
+        ships.insert(symbol.to_string(), ship);
+        behaviors.insert(symbol.to_string(), behavior);
+    }
+    let entries = vec![
+        entry("HAULER", Activity::Sale, 2000.0),
+        entry("HAULER", Activity::Refuel, -100.0),
+        entry("MINER-1", Activity::ShipPurchase, -50_000.0),
+        entry("MINER-2", Activity::Sale, 400.0),
+    ];
+
+    let income = income_by_type(&entries, &ships, &behaviors, Local::now());
+    // 1000 and 1400 over two hours, averaged over both drones
+    assert_eq!(income.get(&ShipType::ShipMiningDrone), Some(&600.0));
+    assert_eq!(income.get(&ShipType::ShipLightHauler), Some(&-50.0));
+}
+
+#[test]
+fn the_fastest_payback_within_the_reserve_is_bought() {
+    let credits = CREDIT_RESERVE + 100_000.0;
+    let plans = vec![
+        plan(ShipType::ShipMiningDrone, 20_000.0, 5_000.0),
+        plan(ShipType::ShipLightHauler, 50_000.0, 25_000.0),
+        // pays back quickest, but would eat into the reserve
+        plan(ShipType::ShipOreHound, 150_000.0, 1_000_000.0),
+        // never pays back in time
+        plan(ShipType::ShipProbe, 1_000.0, 1.0),
+    ];
+
+    let best = best_plan(plans, credits).unwrap();
+    assert_eq!(best.ship_type, ShipType::ShipLightHauler);
+    assert_eq!(best.payback_hours(), 2.0);
+    assert!(best_plan(Vec::new(), credits).is_none());
+}
@@ -0,0 +1,101 @@
+use crate::{
+    prober::choose_post, scheduler::MarketScheduler, store::MarketFreshness, tests::waypoint,
+};
+
+use chrono::{Duration, Local};
+
+fn post(symbol: &str, x: i32, age: Option<Duration>) -> MarketFreshness {
+    MarketFreshness {
+        waypoint: waypoint(symbol),
+        x,
+        y: 0,
+        observed_at: age.map(|age| Local::now() - age),
+        value: 1000.0,
+    }
+}
+
+#[test]
+fn probes_move_on_to_the_stalest_post() {
+    let mut scheduler = MarketScheduler::default();
+    let posts = vec![
+        post("X1-OE-A1", 0, Some(Duration::zero())),
+        post("X1-OE-B2", 50, Some(Duration::days(2))),
+        post("X1-OE-C3", 50, None),
+    ];
+
+    assert_eq!(
+        choose_post(
+            &mut scheduler,
+            "PROBE-1",
+            posts,
+            &waypoint("X1-OE-A1"),
+            0,
+            0
+        ),
+        Some(waypoint("X1-OE-C3"))
+    );
+    assert_eq!(scheduler.assignment("PROBE-1"), Some(&waypoint("X1-OE-C3")));
+}
+
+#[test]
+fn probes_stay_parked_while_everything_is_fresh() {
+    let mut scheduler = MarketScheduler::default();
+    let fresh = || {
+        vec![
+            post("X1-OE-A1", 0, Some(Duration::zero())),
+            post("X1-OE-B2", 50, Some(Duration::zero())),
+        ]
+    };
+
+    assert_eq!(
+        choose_post(
+            &mut scheduler,
+            "PROBE-1",
+            fresh(),
+            &waypoint("X1-OE-A1"),
+            0,
+            0
+        ),
+        Some(waypoint("X1-OE-A1"))
+    );
+    // away from every post there is nowhere to be
+    assert_eq!(
+        choose_post(
+            &mut scheduler,
+            "PROBE-2",
+            fresh(),
+            &waypoint("X1-OE-Z9"),
+            0,
+            0
+        ),
+        None
+    );
+}
+
+#[test]
+fn probes_never_share_a_post() {
+    let mut scheduler = MarketScheduler::default();
+    let here = waypoint("X1-OE-A1");
+    choose_post(
+        &mut scheduler,
+        "PROBE-1",
+        vec![post("X1-OE-A1", 0, Some(Duration::zero()))],
+        &here,
+        0,
+        0,
+    );
+
+    // the post went stale, but PROBE-1 is parked there already
+    let stale = vec![post("X1-OE-A1", 0, Some(Duration::days(2)))];
+    assert_eq!(
+        choose_post(
+            &mut scheduler,
+            "PROBE-2",
+            stale,
+            &waypoint("X1-OE-B2"),
+            0,
+            0
+        ),
+        None
+    );
+}
@@ -0,0 +1,110 @@
+use crate::{
+    reservations::{CargoReservations, Owner},
+    tests::{contract, holding},
+};
+use spacetraders::{enums::TradeSymbol, responses::schemas::Ship};
+
+use chrono::{Duration, Local};
+use std::{collections::HashMap, thread};
+
+fn fleet(ships: Vec<Ship>) -> HashMap<String, Ship> {
+    ships
+        .into_iter()
+        .map(|ship| (ship.symbol.clone(), ship))
+        .collect()
+}
+
+#[test]
+fn contracts_are_covered_by_the_biggest_holders() {
+    let ships = fleet(vec![
+        holding("MINER-1", TradeSymbol::IronOre, 20),
+        holding("MINER-2", TradeSymbol::IronOre, 25),
+    ]);
+    let mut reservations = CargoReservations::default();
+    reservations.reserve_contract(&contract("A"));
+    let iron = TradeSymbol::IronOre;
+
+    assert_eq!(reservations.contract_reserved(&iron), 30);
+    assert_eq!(reservations.spare(&ships, "MINER-2", &iron), 0);
+    assert_eq!(reservations.spare(&ships, "MINER-1", &iron), 15);
+    assert_eq!(
+        reservations.spare(&ships, "MINER-1", &TradeSymbol::CopperOre),
+        0
+    );
+}
+
+#[test]
+fn trade_goods_stay_with_the_trader() {
+    let ships = fleet(vec![
+        holding("TRADER", TradeSymbol::IronOre, 60),
+        holding("MINER", TradeSymbol::IronOre, 20),
+    ]);
+    let trader = Owner::Ship("TRADER".to_string());
+    let mut reservations = CargoReservations::default();
+    reservations.reserve(&trader, &TradeSymbol::IronOre, 60);
+    reservations.reserve_contract(&contract("A"));
+    let iron = TradeSymbol::IronOre;
+
+    // the trader's own reservation is no contract's to take
+    assert_eq!(reservations.contract_reserved(&iron), 30);
+    assert_eq!(reservations.spare(&ships, "TRADER", &iron), 60);
+    assert_eq!(reservations.spare(&ships, "MINER", &iron), 0);
+
+    reservations.release(&trader);
+    assert_eq!(reservations.spare(&ships, "TRADER", &iron), 30);
+    assert_eq!(reservations.spare(&ships, "MINER", &iron), 20);
+}
+
+#[test]
+fn ships_we_dont_know_yet_reserve_only_their_own_hold() {
+    let ships = fleet(vec![holding("MINER", TradeSymbol::IronOre, 20)]);
+    let mut reservations = CargoReservations::default();
+    reservations.reserve(
+        &Owner::Ship("JUST-BOUGHT".to_string()),
+        &TradeSymbol::IronOre,
+        50,
+    );
+    assert_eq!(reservations.contract_reserved(&TradeSymbol::IronOre), 0);
+    assert_eq!(
+        reservations.spare(&ships, "MINER", &TradeSymbol::IronOre),
+        20
+    );
+}
+
+#[test]
+fn expired_contracts_release_their_goods() {
+    let ships = fleet(vec![holding("MINER", TradeSymbol::IronOre, 20)]);
+    let iron = TradeSymbol::IronOre;
+    let mut reservations = CargoReservations::default();
+    let mut expiring = contract("A");
+    expiring.terms.deadline = Local::now() + Duration::milliseconds(20);
+    reservations.reserve_contract(&expiring);
+    assert_eq!(reservations.spare(&ships, "MINER", &iron), 0);
+
+    thread::sleep(std::time::Duration::from_millis(40));
+    // nothing else happened to the reservations, yet A no longer counts
+    assert_eq!(reservations.spare(&ships, "MINER", &iron), 20);
+    reservations.release_expired();
+    assert_eq!(
+        reservations.reserved_by(&Owner::Contract("A".to_string()), &iron),
+        0
+    );
+
+    let mut other = contract("B");
+    other.terms.deliver[0].units_fulfilled = 35;
+    reservations.reserve_contract(&other);
+    assert_eq!(reservations.contract_reserved(&iron), 5);
+    assert_eq!(reservations.spare(&ships, "MINER", &iron), 15);
+}
+
+#[test]
+fn only_one_hauler_claims_a_ship() {
+    let mut reservations = CargoReservations::default();
+    assert!(reservations.claim("HAULER-1", "MINER"));
+    assert!(reservations.claim("HAULER-1", "MINER"));
+    assert!(!reservations.claim("HAULER-2", "MINER"));
+    assert!(reservations.claimed_by_other("HAULER-2", "MINER"));
+
+    reservations.release_claims("HAULER-1");
+    assert!(reservations.claim("HAULER-2", "MINER"));
+}
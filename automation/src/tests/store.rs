@@ -0,0 +1,246 @@
+use crate::{
+    ledger::{Activity, LedgerEntry, LedgerFilter},
+    store::Store,
+    tests::{waypoint, waypoint_at},
+};
+use spacetraders::{
+    enums::{GetMarketSupplyType, ShipType, TradeSymbol},
+    responses::schemas::{GetMarketTradeGood, JumpGate, Market, Shipyard, Waypoint},
+    SystemString,
+};
+
+use chrono::{DateTime, Duration, Local};
+use std::{env, fs, thread};
+
+fn store() -> Store {
+    Store::open_path(":memory:", Local::now())
+}
+
+fn market(goods: &[(TradeSymbol, f64, f64)]) -> Market {
+    Market {
+        symbol: String::new(),
+        exports: Vec::new(),
+        imports: Vec::new(),
+        exchange: Vec::new(),
+        transactions: Vec::new(),
+        trade_goods: goods
+            .iter()
+            .map(|(symbol, purchase_price, sell_price)| GetMarketTradeGood {
+                symbol: symbol.clone(),
+                trade_volume: 10,
+                supply: GetMarketSupplyType::Moderate,
+                purchase_price: *purchase_price,
+                sell_price: *sell_price,
+            })
+            .collect(),
+    }
+}
+
+fn entry(ship: &str, activity: Activity, total: f64, recorded_at: DateTime<Local>) -> LedgerEntry {
+    LedgerEntry {
+        recorded_at,
+        ship: ship.to_string(),
+        activity,
+        good: "FUEL".to_string(),
+        units: 1,
+        price_per_unit: total.abs(),
+        total,
+        balance: 0.0,
+    }
+}
+
+fn system() -> SystemString {
+    system_named("X1-OE")
+}
+
+fn system_named(symbol: &str) -> SystemString {
+    serde_json::from_value(serde_json::Value::String(symbol.to_string())).unwrap()
+}
+
+fn waypoint_in(symbol: &str, r#type: &str, under_construction: bool) -> Waypoint {
+    let system = &symbol[..symbol.rfind('-').unwrap()];
+    serde_json::from_str(&format!(
+        r#"{{"systemSymbol":"{system}","symbol":"{symbol}","type":"{type}","x":0,"y":0,
+            "orbitals":[],"traits":[],"isUnderConstruction":{under_construction}}}"#
+    ))
+    .unwrap()
+}
+
+fn jump_gate(connected: &str) -> JumpGate {
+    serde_json::from_str(&format!(
+        r#"{{"jumpRange":500,"connectedSystems":[{{"symbol":"{connected}","sectorSymbol":"X1",
+            "type":"RED_STAR","x":0,"y":0,"distance":100}}]}}"#
+    ))
+    .unwrap()
+}
+
+// a shipyard with a mining drone in stock at the price
+fn shipyard(price: f64) -> Shipyard {
+    serde_json::from_str(&format!(
+        r#"{{"symbol":"","shipTypes":[{{"type":"SHIP_MINING_DRONE"}}],
+            "ships":[{{"type":"SHIP_MINING_DRONE","name":"Drone","purchasePrice":{price},
+                "frame":{{"symbol":"FRAME_DRONE","name":"Drone","moduleSlots":2,
+                    "mountingPoints":2,"fuelCapacity":100,"requirements":{{"power":1}}}},
+                "reactor":{{"symbol":"REACTOR_CHEMICAL_I","name":"Reactor","powerOutput":10,
+                    "requirements":{{}}}},
+                "engine":{{"symbol":"ENGINE_IMPULSE_DRIVE_I","name":"Engine","speed":10,
+                    "requirements":{{"power":1}}}},
+                "modules":[],"mounts":[]}}]}}"#
+    ))
+    .unwrap()
+}
+
+#[test]
+fn prices_come_from_the_latest_observations() {
+    let store = store();
+    let (a1, a2) = (
+        waypoint_at("X1-OE-A1", 0, 0),
+        waypoint_at("X1-OE-A2", 10, 0),
+    );
+    store.record_market(&a1, &market(&[(TradeSymbol::Fuel, 80.0, 70.0)]));
+    store.record_market(&a2, &market(&[(TradeSymbol::Fuel, 100.0, 90.0)]));
+    thread::sleep(std::time::Duration::from_millis(5));
+    store.record_market(&a1, &market(&[(TradeSymbol::Fuel, 120.0, 110.0)]));
+    let fuel = TradeSymbol::Fuel;
+
+    let latest = store.latest_price(&a1.symbol, &fuel).unwrap();
+    assert_eq!(latest.purchase_price, 120.0);
+    assert_eq!(store.latest_prices(&fuel).len(), 2);
+    assert_eq!(
+        store
+            .best_purchase_price(&system(), &fuel)
+            .unwrap()
+            .waypoint,
+        a2.symbol
+    );
+    assert_eq!(
+        store.best_sell_price(&system(), &fuel).unwrap().waypoint,
+        a1.symbol
+    );
+    let history = store.price_history(&a1.symbol, &fuel, Local::now() - Duration::hours(1));
+    assert_eq!(history.len(), 2);
+    assert!(store
+        .latest_price(&a1.symbol, &TradeSymbol::IronOre)
+        .is_none());
+
+    store.forget_good(&a2.symbol, &fuel);
+    assert!(store.latest_price(&a2.symbol, &fuel).is_none());
+    assert_eq!(store.latest_prices(&fuel).len(), 1);
+}
+
+#[test]
+fn ledger_filters_and_sums_profit() {
+    let store = store();
+    let now = Local::now();
+    store.record_ledger_entry(&entry("SHIP-1", Activity::Sale, 500.0, now));
+    store.record_ledger_entry(&entry("SHIP-1", Activity::Refuel, -100.0, now));
+    store.record_ledger_entry(&entry("SHIP-2", Activity::Sale, 300.0, now));
+    store.record_ledger_entry(&entry(
+        "SHIP-2",
+        Activity::Sale,
+        1000.0,
+        now - Duration::days(2),
+    ));
+
+    assert_eq!(store.ledger(&LedgerFilter::default()).len(), 4);
+    let filter = LedgerFilter {
+        ship: Some("SHIP-1".to_string()),
+        ..LedgerFilter::since(now - Duration::hours(1))
+    };
+    assert_eq!(store.ledger(&filter).len(), 2);
+    let filter = LedgerFilter {
+        activity: Some(Activity::Sale),
+        ..LedgerFilter::since(now - Duration::hours(1))
+    };
+    assert_eq!(store.ledger(&filter).len(), 2);
+
+    let by_ship = store.profit_by_ship(now - Duration::hours(1));
+    assert_eq!(by_ship["SHIP-1"].income, 500.0);
+    assert_eq!(by_ship["SHIP-1"].spending, 100.0);
+    assert_eq!(by_ship["SHIP-1"].profit(), 400.0);
+    assert_eq!(by_ship["SHIP-2"].transactions, 1);
+    let by_activity = store.profit_by_activity(now - Duration::hours(1));
+    assert_eq!(by_activity[&Activity::Sale].income, 800.0);
+    assert_eq!(by_activity[&Activity::Refuel].spending, 100.0);
+}
+
+#[test]
+fn a_reset_keeps_only_the_ledger() {
+    let dir = env::temp_dir().join(format!("automation-store-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("universe.db");
+    let before = Local::now() - Duration::days(7);
+    {
+        let store = Store::open_path(&path, before);
+        store.record_waypoints(&[waypoint_at("X1-OE-A1", 0, 0)]);
+        store.record_ledger_entry(&entry("SHIP-1", Activity::Sale, 500.0, Local::now()));
+    }
+
+    let store = Store::open_path(&path, Local::now());
+    assert!(store.waypoint(&waypoint("X1-OE-A1")).is_none());
+    assert_eq!(store.ledger(&LedgerFilter::default()).len(), 1);
+    drop(store);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn an_offline_store_has_to_exist() {
+    let dir = env::temp_dir().join(format!("automation-missing-{}", std::process::id()));
+    assert!(Store::open_offline(&dir).is_err());
+    assert!(!dir.exists());
+}
+
+// X1-OE -> X1-AB -> X1-CD, where the gate out of X1-AB may still be under construction
+fn gate_network(under_construction: bool) -> Store {
+    let store = store();
+    store.record_waypoints(&[
+        waypoint_in("X1-OE-G1", "JUMP_GATE", false),
+        waypoint_in("X1-AB-G1", "JUMP_GATE", under_construction),
+    ]);
+    store.record_jump_gate(&waypoint("X1-OE-G1"), &jump_gate("X1-AB"));
+    store.record_jump_gate(&waypoint("X1-AB-G1"), &jump_gate("X1-CD"));
+    for (symbol, price) in [("X1-AB-S1", 30_000.0), ("X1-CD-S1", 10_000.0)] {
+        store.record_shipyard(&waypoint_in(symbol, "PLANET", false), &shipyard(price));
+    }
+    store
+}
+
+#[test]
+fn the_cheapest_shipyard_is_the_cheapest_within_reach() {
+    let store = gate_network(false);
+    let drone = ShipType::ShipMiningDrone;
+    let home = system_named("X1-OE");
+
+    let (offer, jumps) = store.cheapest_shipyard(&drone, &home, 3).unwrap();
+    assert_eq!((offer.waypoint, jumps), (waypoint("X1-CD-S1"), 2));
+    assert_eq!(offer.purchase_price, Some(10_000.0));
+    assert_eq!(
+        store.jump_route(&home, &system_named("X1-CD"), 3),
+        Some(vec![
+            (waypoint("X1-OE-G1"), system_named("X1-AB")),
+            (waypoint("X1-AB-G1"), system_named("X1-CD")),
+        ])
+    );
+
+    let (offer, jumps) = store.cheapest_shipyard(&drone, &home, 1).unwrap();
+    assert_eq!((offer.waypoint, jumps), (waypoint("X1-AB-S1"), 1));
+    assert!(store
+        .cheapest_shipyard(&ShipType::ShipProbe, &home, 3)
+        .is_none());
+}
+
+#[test]
+fn gates_under_construction_are_not_jumped_through() {
+    let store = gate_network(true);
+    let home = system_named("X1-OE");
+
+    assert_eq!(
+        store.reachable_systems(&home, 3),
+        vec![(home.clone(), 0), (system_named("X1-AB"), 1)]
+    );
+    assert!(store.jump_route(&home, &system_named("X1-CD"), 3).is_none());
+    let (offer, _) = store
+        .cheapest_shipyard(&ShipType::ShipMiningDrone, &home, 3)
+        .unwrap();
+    assert_eq!(offer.waypoint, waypoint("X1-AB-S1"));
+}
@@ -0,0 +1,74 @@
+use crate::{
+    store::PriceObservation,
+    tests::{ship, waypoint},
+    trader::rank_routes,
+};
+use spacetraders::enums::{GetMarketSupplyType, TradeSymbol};
+
+use chrono::Local;
+use std::collections::HashMap;
+
+fn price(
+    market: &str,
+    symbol: TradeSymbol,
+    purchase_price: f64,
+    sell_price: f64,
+) -> PriceObservation {
+    PriceObservation {
+        waypoint: waypoint(market),
+        symbol,
+        observed_at: Local::now(),
+        supply: GetMarketSupplyType::Abundant,
+        trade_volume: 10,
+        purchase_price,
+        sell_price,
+    }
+}
+
+#[test]
+fn routes_are_ranked_by_profit_per_hour() {
+    let mut trader = ship();
+    trader.cargo.capacity = 100;
+    let coordinates = HashMap::from([
+        (waypoint("X1-OE-A1"), (0, 0)),
+        (waypoint("X1-OE-B2"), (100, 0)),
+        (waypoint("X1-OE-C3"), (10, 0)),
+    ]);
+    let prices = vec![
+        price("X1-OE-A1", TradeSymbol::IronOre, 10.0, 8.0),
+        price("X1-OE-B2", TradeSymbol::IronOre, 35.0, 30.0),
+        price("X1-OE-A1", TradeSymbol::CopperOre, 10.0, 8.0),
+        price("X1-OE-C3", TradeSymbol::CopperOre, 30.0, 25.0),
+        // nobody pays more for gold than it costs
+        price("X1-OE-A1", TradeSymbol::Gold, 50.0, 40.0),
+        price("X1-OE-C3", TradeSymbol::Gold, 60.0, 45.0),
+    ];
+
+    let routes = rank_routes(&trader, &prices, &coordinates, 0.0, 1_000_000.0);
+    let symbols: Vec<_> = routes.iter().map(|route| route.symbol.clone()).collect();
+    assert_eq!(symbols, vec![TradeSymbol::CopperOre, TradeSymbol::IronOre]);
+    // the far route earns more, but takes too long to be worth it
+    assert!(routes[1].profit > routes[0].profit);
+    assert!(routes[0].profit_per_hour() > routes[1].profit_per_hour());
+    assert_eq!(routes[0].units, 30);
+    assert_eq!(routes[0].buy.waypoint, waypoint("X1-OE-A1"));
+    assert_eq!(routes[0].sell.waypoint, waypoint("X1-OE-C3"));
+}
+
+#[test]
+fn routes_are_limited_by_credits() {
+    let mut trader = ship();
+    trader.cargo.capacity = 100;
+    let coordinates = HashMap::from([
+        (waypoint("X1-OE-A1"), (0, 0)),
+        (waypoint("X1-OE-C3"), (10, 0)),
+    ]);
+    let prices = vec![
+        price("X1-OE-A1", TradeSymbol::CopperOre, 10.0, 8.0),
+        price("X1-OE-C3", TradeSymbol::CopperOre, 30.0, 25.0),
+    ];
+
+    let routes = rank_routes(&trader, &prices, &coordinates, 0.0, 55.0);
+    assert_eq!(routes[0].units, 5);
+    assert!(rank_routes(&trader, &prices, &coordinates, 0.0, 5.0).is_empty());
+}
@@ -0,0 +1,276 @@
+use super::{
+    behavior::ShipBehavior,
+    func::{travel_seconds, ShipAutomation, FUEL_PER_MARKET_UNIT},
+    hauler,
+    reservations::Owner,
+    seller,
+    store::PriceObservation,
+};
+use spacetraders::{enums, responses::schemas::Ship, WaypointString};
+
+use async_trait::async_trait;
+use log::{info, trace};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+};
+use tokio::{
+    sync::mpsc,
+    time::{sleep, Duration},
+};
+
+// prices move after every trade volume, so don't plan on more than this many per trip
+const VOLUMES_PER_TRIP: i32 = 3;
+// time spent docking, trading and undocking at both ends of a route
+const DOCKING_SECONDS: f64 = 60.0;
+
+// Buying a good at one market to sell it at another, as we expect it to go.
+#[derive(Debug, Clone)]
+pub struct TradeRoute {
+    pub symbol: enums::TradeSymbol,
+    pub buy: PriceObservation,
+    pub sell: PriceObservation,
+    pub units: i32,
+    // after fuel, discounted by supply and how much we trust the prices
+    pub profit: f64,
+    pub duration: chrono::Duration,
+}
+impl TradeRoute {
+    pub fn profit_per_hour(&self) -> f64 {
+        let hours = self.duration.num_seconds().max(60) as f64 / 3600.0;
+        self.profit / hours
+    }
+}
+
+// every buy here, sell there opportunity in the ship's system, best profit per hour first
+pub async fn find_routes(ship_automation: &ShipAutomation, ship: &Ship) -> Vec<TradeRoute> {
+    trace!("Find Routes");
+    let system = &ship.nav.system_symbol;
+    let (prices, coordinates, fuel_price) = {
+        let unlocked = ship_automation.read().await;
+        let store = &unlocked.automation_data.store;
+        let coordinates: HashMap<WaypointString, (i32, i32)> = store
+            .waypoints_in_system(system)
+            .into_iter()
+            .map(|waypoint| (waypoint.symbol, (waypoint.x, waypoint.y)))
+            .collect();
+        let fuel_price = store
+            .best_purchase_price(system, &enums::TradeSymbol::Fuel)
+            .map_or(0.0, |price| price.purchase_price / FUEL_PER_MARKET_UNIT);
+        (store.system_prices(system), coordinates, fuel_price)
+    };
+    let credits = ship_automation.get_credits().await;
+    rank_routes(ship, &prices, &coordinates, fuel_price, credits)
+}
+
+// the routes the prices offer a ship with these credits, best profit per hour first
+pub(crate) fn rank_routes(
+    ship: &Ship,
+    prices: &[PriceObservation],
+    coordinates: &HashMap<WaypointString, (i32, i32)>,
+    fuel_price: f64,
+    credits: f64,
+) -> Vec<TradeRoute> {
+    let Some(&here) = coordinates.get(&ship.nav.waypoint_symbol) else {
+        return Vec::new();
+    };
+    let distance = |(x1, y1): (i32, i32), (x2, y2): (i32, i32)| {
+        (((x1 - x2) as f64).powi(2) + ((y1 - y2) as f64).powi(2))
+            .sqrt()
+            .round()
+            .max(1.0)
+    };
+
+    let mut routes = Vec::new();
+    for buy in prices.iter() {
+        for sell in prices.iter() {
+            if buy.symbol != sell.symbol
+                || buy.waypoint == sell.waypoint
+                || sell.sell_price <= buy.purchase_price
+            {
+                continue;
+            }
+            let (Some(&from), Some(&to)) = (
+                coordinates.get(&buy.waypoint),
+                coordinates.get(&sell.waypoint),
+            ) else {
+                continue;
+            };
+
+            let units = ship
+                .cargo
+                .capacity
+                .min(buy.trade_volume.min(sell.trade_volume) as i32 * VOLUMES_PER_TRIP)
+                .min((credits / buy.purchase_price) as i32);
+            if units <= 0 {
+                continue;
+            }
+
+            let (to_market, to_buyer) = (distance(here, from), distance(from, to));
+            let margin = (sell.sell_price - buy.purchase_price)
+                * units as f64
+                * supply_factor(&buy.supply)
+                * buy.confidence().min(sell.confidence());
+            let profit = margin - (to_market + to_buyer) * fuel_price;
+            let seconds = travel_seconds(to_market, ship.engine.speed)
+                + travel_seconds(to_buyer, ship.engine.speed)
+                + DOCKING_SECONDS;
+            if profit > 0.0 {
+                routes.push(TradeRoute {
+                    symbol: buy.symbol.clone(),
+                    buy: buy.clone(),
+                    sell: sell.clone(),
+                    units,
+                    profit,
+                    duration: chrono::Duration::seconds(seconds as i64),
+                });
+            }
+        }
+    }
+    routes.sort_by(|a, b| b.profit_per_hour().total_cmp(&a.profit_per_hour()));
+    routes
+}
+
+// scarce goods get pricier with every unit we buy
+fn supply_factor(supply: &enums::GetMarketSupplyType) -> f64 {
+    match supply {
+        enums::GetMarketSupplyType::Scarce => 0.5,
+        enums::GetMarketSupplyType::Limited => 0.75,
+        enums::GetMarketSupplyType::Moderate => 0.9,
+        enums::GetMarketSupplyType::Abundant => 1.0,
+    }
+}
+
+// Runs the best trade route it can find, then sells wherever pays the most
+// once it gets there, re-planning from fresh prices every time.
+#[derive(Debug, Default)]
+pub struct Trader {
+    // the route every trader is on, so two don't drain the same market
+    routes: Mutex<HashMap<String, TradeRoute>>,
+}
+impl Trader {
+    pub const NAME: &'static str = "trader";
+}
+#[async_trait]
+impl ShipBehavior for Trader {
+    fn name(&self) -> &'static str {
+        Trader::NAME
+    }
+    async fn run(&self, ship_automation: &mut ShipAutomation, _channel: &mpsc::Sender<Ship>) {
+        let ship = ship_automation.clone_ship().await.unwrap();
+        if ship.cargo.units > 0 {
+            self.sell(ship_automation, &ship).await;
+            return;
+        }
+
+        let taken: HashSet<(enums::TradeSymbol, WaypointString)> = self
+            .routes
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(ship_id, _)| **ship_id != ship_automation.ship_id)
+            .map(|(_, route)| (route.symbol.clone(), route.buy.waypoint.clone()))
+            .collect();
+        let route = find_routes(ship_automation, &ship)
+            .await
+            .into_iter()
+            .find(|route| !taken.contains(&(route.symbol.clone(), route.buy.waypoint.clone())));
+        let Some(route) = route else {
+            sleep(Duration::from_secs(60)).await;
+            return;
+        };
+
+        info!(
+            "{} trades {} {:?} from {} to {} for about {:.0} credits an hour",
+            ship_automation.ship_id,
+            route.units,
+            route.symbol,
+            route.buy.waypoint.waypoint,
+            route.sell.waypoint.waypoint,
+            route.profit_per_hour()
+        );
+        self.routes
+            .lock()
+            .unwrap()
+            .insert(ship_automation.ship_id.clone(), route.clone());
+        let bought = self.buy(ship_automation, &route).await;
+        if bought == 0 {
+            self.routes.lock().unwrap().remove(&ship_automation.ship_id);
+        } else {
+            // keeps the goods from being sold anywhere but where we planned
+            ship_automation
+                .write()
+                .await
+                .automation_data
+                .reservations
+                .reserve(
+                    &Owner::Ship(ship_automation.ship_id.clone()),
+                    &route.symbol,
+                    bought,
+                );
+        }
+    }
+}
+impl Trader {
+    // buys the route's goods unless the price moved against us on the way
+    async fn buy(&self, ship_automation: &ShipAutomation, route: &TradeRoute) -> i32 {
+        trace!("Buy Route Goods");
+        if !ship_automation.ensure_docked_at(&route.buy.waypoint).await {
+            return 0;
+        }
+        let price = latest_price(ship_automation, &route.buy.waypoint, &route.symbol).await;
+        match price {
+            Some(price) if price.purchase_price < route.sell.sell_price => {
+                ship_automation
+                    .purchase_cargo_in_chunks(&route.symbol, route.units, price.trade_volume)
+                    .await
+            }
+            _ => {
+                info!(
+                    "{} {:?} no longer pays to buy at {}",
+                    ship_automation.ship_id, route.symbol, route.buy.waypoint.waypoint
+                );
+                0
+            }
+        }
+    }
+
+    // sells everything at whichever market pays the most for it right now
+    async fn sell(&self, ship_automation: &mut ShipAutomation, ship: &Ship) {
+        trace!("Sell Route Goods");
+        // our own reservation doesn't keep us from selling, it keeps the goods
+        // from covering a contract until they are sold
+        for item in ship.cargo.inventory.iter() {
+            let ship = ship_automation.clone_ship().await.unwrap();
+            seller::sell_item(ship_automation, &ship, item).await;
+        }
+        ship_automation
+            .write()
+            .await
+            .automation_data
+            .reservations
+            .release(&Owner::Ship(ship_automation.ship_id.clone()));
+        self.routes.lock().unwrap().remove(&ship_automation.ship_id);
+
+        // whatever no known market buys
+        if ship_automation.clone_ship().await.unwrap().cargo.units > 0 {
+            hauler::unload(ship_automation).await;
+        }
+    }
+}
+
+// refreshes the market we're docked at and returns its price for the good
+async fn latest_price(
+    ship_automation: &ShipAutomation,
+    waypoint: &WaypointString,
+    symbol: &enums::TradeSymbol,
+) -> Option<PriceObservation> {
+    let waypoint = ship_automation.get_waypoint(waypoint).await;
+    ship_automation.get_market(&waypoint).await;
+    ship_automation
+        .read()
+        .await
+        .automation_data
+        .store
+        .latest_price(&waypoint.symbol, symbol)
+}
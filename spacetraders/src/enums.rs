@@ -115,7 +115,7 @@ pub enum SystemType {
     Untagged(String),
 }
 
-#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Clone, Debug)]
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Hash, Clone, Debug)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ShipType {
     ShipProbe,
@@ -134,7 +134,7 @@ pub enum ShipType {
     Untagged(String),
 }
 
-#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Clone, Debug)]
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Hash, Clone, Debug)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ShipRole {
     Fabricator,
@@ -206,7 +206,7 @@ pub enum ShipMount {
     Untagged(String),
 }
 
-#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Clone, Debug)]
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Hash, Clone, Debug)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum TradeSymbol {
     PreciousStones,
@@ -526,3 +526,254 @@ pub enum DepositSize {
     Moderate,
     Large,
 }
+
+// What sort of good a trade symbol is.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum GoodCategory {
+    // comes out of asteroids and gas giants
+    RawOre,
+    // what a refinery makes out of raw ore
+    Refined,
+    Fuel,
+    // mounts, modules, reactors and engines
+    Equipment,
+    Antimatter,
+    // everything made in factories or found
+    Other,
+}
+
+// The kind of mount that extracts a raw good.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Extractor {
+    MiningLaser,
+    GasSiphon,
+}
+
+impl TradeSymbol {
+    pub fn category(&self) -> GoodCategory {
+        if self.extractor().is_some() {
+            return GoodCategory::RawOre;
+        }
+        match self {
+            TradeSymbol::Iron
+            | TradeSymbol::Copper
+            | TradeSymbol::Aluminum
+            | TradeSymbol::Silver
+            | TradeSymbol::Gold
+            | TradeSymbol::Platinum
+            | TradeSymbol::Uranite
+            | TradeSymbol::Meritium => GoodCategory::Refined,
+            TradeSymbol::Fuel => GoodCategory::Fuel,
+            TradeSymbol::Antimatter => GoodCategory::Antimatter,
+            _ if self.mount().is_some()
+                || self.module().is_some()
+                || self.reactor().is_some()
+                || self.engine().is_some() =>
+            {
+                GoodCategory::Equipment
+            }
+            _ => GoodCategory::Other,
+        }
+    }
+
+    // what a refinery makes out of the good
+    pub fn refines_into(&self) -> Option<TradeSymbol> {
+        match self {
+            TradeSymbol::IronOre => Some(TradeSymbol::Iron),
+            TradeSymbol::CopperOre => Some(TradeSymbol::Copper),
+            TradeSymbol::AluminumOre => Some(TradeSymbol::Aluminum),
+            TradeSymbol::SilverOre => Some(TradeSymbol::Silver),
+            TradeSymbol::GoldOre => Some(TradeSymbol::Gold),
+            TradeSymbol::PlatinumOre => Some(TradeSymbol::Platinum),
+            TradeSymbol::UraniteOre => Some(TradeSymbol::Uranite),
+            TradeSymbol::MeritiumOre => Some(TradeSymbol::Meritium),
+            TradeSymbol::Hydrocarbon => Some(TradeSymbol::Fuel),
+            _ => None,
+        }
+    }
+
+    pub fn is_refinable(&self) -> bool {
+        self.refines_into().is_some()
+    }
+
+    // the mount that extracts the good, None for anything that isn't mined
+    pub fn extractor(&self) -> Option<Extractor> {
+        match self {
+            TradeSymbol::PreciousStones
+            | TradeSymbol::QuartzSand
+            | TradeSymbol::SiliconCrystals
+            | TradeSymbol::AmmoniaIce
+            | TradeSymbol::IceWater
+            | TradeSymbol::IronOre
+            | TradeSymbol::CopperOre
+            | TradeSymbol::AluminumOre
+            | TradeSymbol::SilverOre
+            | TradeSymbol::GoldOre
+            | TradeSymbol::PlatinumOre
+            | TradeSymbol::Diamonds
+            | TradeSymbol::UraniteOre
+            | TradeSymbol::MeritiumOre => Some(Extractor::MiningLaser),
+            TradeSymbol::LiquidHydrogen
+            | TradeSymbol::LiquidNitrogen
+            | TradeSymbol::Hydrocarbon => Some(Extractor::GasSiphon),
+            _ => None,
+        }
+    }
+}
+
+// Equipment is bought and sold at markets as the trade good of the same name.
+macro_rules! trade_good_conversions {
+    ($equipment:ident, $from_trade_symbol:ident, [$($variant:ident),* $(,)?]) => {
+        impl $equipment {
+            pub fn trade_symbol(&self) -> Option<TradeSymbol> {
+                match self {
+                    $($equipment::$variant => Some(TradeSymbol::$variant),)*
+                    _ => None,
+                }
+            }
+        }
+        impl TradeSymbol {
+            pub fn $from_trade_symbol(&self) -> Option<$equipment> {
+                match self {
+                    $(TradeSymbol::$variant => Some($equipment::$variant),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+trade_good_conversions!(
+    ShipMount,
+    mount,
+    [
+        MountGasSiphonI,
+        MountGasSiphonIi,
+        MountGasSiphonIii,
+        MountSurveyorI,
+        MountSurveyorIi,
+        MountSurveyorIii,
+        MountSensorArrayI,
+        MountSensorArrayIi,
+        MountSensorArrayIii,
+        MountMiningLaserI,
+        MountMiningLaserIi,
+        MountMiningLaserIii,
+        MountLaserCannonI,
+        MountMissileLauncherI,
+        MountTurretI,
+    ]
+);
+trade_good_conversions!(
+    ShipModule,
+    module,
+    [
+        ModuleMineralProcessorI,
+        ModuleCargoHoldI,
+        ModuleCrewQuartersI,
+        ModuleEnvoyQuartersI,
+        ModulePassengerCabinI,
+        ModuleMicroRefineryI,
+        ModuleOreRefineryI,
+        ModuleFuelRefineryI,
+        ModuleScienceLabI,
+        ModuleJumpDriveI,
+        ModuleJumpDriveIi,
+        ModuleJumpDriveIii,
+        ModuleWarpDriveI,
+        ModuleWarpDriveIi,
+        ModuleWarpDriveIii,
+        ModuleShieldGeneratorI,
+        ModuleShieldGeneratorIi,
+    ]
+);
+trade_good_conversions!(
+    ShipReactor,
+    reactor,
+    [
+        ReactorSolarI,
+        ReactorFusionI,
+        ReactorFissionI,
+        ReactorChemicalI,
+        ReactorAntimatterI,
+    ]
+);
+trade_good_conversions!(
+    ShipEngine,
+    engine,
+    [
+        EngineImpulseDriveI,
+        EngineIonDriveI,
+        EngineIonDriveIi,
+        EngineHyperDriveI,
+    ]
+);
+
+impl ShipMount {
+    // lasers and siphons, None for mounts that don't extract
+    pub fn extractor(&self) -> Option<Extractor> {
+        match self {
+            ShipMount::MountMiningLaserI
+            | ShipMount::MountMiningLaserIi
+            | ShipMount::MountMiningLaserIii => Some(Extractor::MiningLaser),
+            ShipMount::MountGasSiphonI
+            | ShipMount::MountGasSiphonIi
+            | ShipMount::MountGasSiphonIii => Some(Extractor::GasSiphon),
+            _ => None,
+        }
+    }
+
+    pub fn is_surveyor(&self) -> bool {
+        matches!(
+            self,
+            ShipMount::MountSurveyorI | ShipMount::MountSurveyorIi | ShipMount::MountSurveyorIii
+        )
+    }
+
+    pub fn is_sensor_array(&self) -> bool {
+        matches!(
+            self,
+            ShipMount::MountSensorArrayI
+                | ShipMount::MountSensorArrayIi
+                | ShipMount::MountSensorArrayIii
+        )
+    }
+}
+
+impl ShipFrame {
+    // the ship type built on the frame, refining freighters share theirs with
+    // heavy freighters
+    pub fn ship_type(&self) -> Option<ShipType> {
+        match self {
+            ShipFrame::FrameProbe => Some(ShipType::ShipProbe),
+            ShipFrame::FrameDrone => Some(ShipType::ShipMiningDrone),
+            ShipFrame::FrameInterceptor => Some(ShipType::ShipInterceptor),
+            ShipFrame::FrameLightFreighter => Some(ShipType::ShipLightHauler),
+            ShipFrame::FrameFrigate => Some(ShipType::ShipCommandFrigate),
+            ShipFrame::FrameExplorer => Some(ShipType::ShipExplorer),
+            ShipFrame::FrameShuttle => Some(ShipType::ShipLightShuttle),
+            ShipFrame::FrameMiner => Some(ShipType::ShipOreHound),
+            ShipFrame::FrameHeavyFreighter => Some(ShipType::ShipHeavyFreighter),
+            _ => None,
+        }
+    }
+}
+
+impl ShipType {
+    pub fn frame(&self) -> Option<ShipFrame> {
+        match self {
+            ShipType::ShipProbe => Some(ShipFrame::FrameProbe),
+            ShipType::ShipMiningDrone => Some(ShipFrame::FrameDrone),
+            ShipType::ShipInterceptor => Some(ShipFrame::FrameInterceptor),
+            ShipType::ShipLightHauler => Some(ShipFrame::FrameLightFreighter),
+            ShipType::ShipCommandFrigate => Some(ShipFrame::FrameFrigate),
+            ShipType::ShipExplorer => Some(ShipFrame::FrameExplorer),
+            ShipType::ShipLightShuttle => Some(ShipFrame::FrameShuttle),
+            ShipType::ShipOreHound => Some(ShipFrame::FrameMiner),
+            ShipType::ShipHeavyFreighter | ShipType::ShipRefiningFreighter => {
+                Some(ShipFrame::FrameHeavyFreighter)
+            }
+            _ => None,
+        }
+    }
+}
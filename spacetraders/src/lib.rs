@@ -4,9 +4,9 @@ pub mod responses;
 mod tests;
 
 use requests::{
-    DeliverCargoToContract, InstallMount, JettisonCargo, JumpShip, NavigateShip, PatchShipNav,
-    PurchaseCargo, PurchaseShip, RegisterNewAgent, RemoveMount, Requests, SellCargo, ShipRefine,
-    TransferCargo, WarpShip,
+    DeliverCargoToContract, InstallModule, InstallMount, JettisonCargo, JumpShip, NavigateShip,
+    PatchShipNav, PurchaseCargo, PurchaseShip, RegisterNewAgent, RemoveModule, RemoveMount,
+    Requests, SellCargo, ShipRefine, TransferCargo, WarpShip,
 };
 use responses::{
     schemas, GetStatus, {agents, contracts, factions, fleet, systems},
@@ -15,20 +15,23 @@ use responses::{
 use async_recursion::async_recursion;
 use chrono::{DateTime, Duration, Local};
 use core::panic;
+use futures_util::StreamExt;
 use log::error;
 use random_string::generate;
 use reqwest::{
     header::{HeaderValue, AUTHORIZATION, CONTENT_LENGTH},
-    Client,
+    Client, Response,
 };
 use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
 use reqwest_retry::{policies::ExponentialBackoff, RetryTransientMiddleware};
 use serde::{
     de::{Error as OtherError, Unexpected},
-    Deserialize, Deserializer, Serialize,
+    Deserialize, Deserializer, Serialize, Serializer,
 };
+use std::io;
 use thiserror::Error;
-use tokio::time::sleep;
+use tokio::{task, time::sleep};
+use tokio_util::io::{StreamReader, SyncIoBridge};
 use url::Url;
 
 const LIVEURL: &str = "https://api.spacetraders.io/v2";
@@ -93,7 +96,7 @@ impl SpaceTraders {
         };
 
         match Client::new()
-            .post(&format!("{}/register", LIVEURL))
+            .post(format!("{}/register", LIVEURL))
             .json(&post_message)
             .send()
             .await
@@ -130,9 +133,7 @@ impl SpaceTraders {
         let last_request = self.last_request;
         let now = Local::now();
         let duration = Duration::seconds(1);
-        if now > last_request && last_request + duration <= now {
-            return;
-        } else {
+        if now <= last_request || last_request + duration > now {
             let time_to_sleep = now - (last_request + duration);
             sleep(
                 time_to_sleep
@@ -140,7 +141,6 @@ impl SpaceTraders {
                     .expect("Failed to convert chrono::Duration to std::time::Duration"),
             )
             .await;
-            return;
         }
     }
 
@@ -151,6 +151,21 @@ impl SpaceTraders {
         url: &str,
         data: Option<Requests>,
     ) -> Option<String> {
+        match self.send_reqwest(method, url, data).await?.text().await {
+            Err(err) => {
+                error!("Error from response: {}", err);
+                None
+            }
+            Ok(msg) => Some(msg),
+        }
+    }
+
+    async fn send_reqwest(
+        &self,
+        method: Method,
+        url: &str,
+        data: Option<Requests>,
+    ) -> Option<Response> {
         self.ensure_request_limit().await;
 
         let mut client = match method {
@@ -187,6 +202,8 @@ impl SpaceTraders {
                 Requests::TransferCargo(json) => client.json(&json),
                 Requests::InstallMount(json) => client.json(&json),
                 Requests::RemoveMount(json) => client.json(&json),
+                Requests::InstallModule(json) => client.json(&json),
+                Requests::RemoveModule(json) => client.json(&json),
                 Requests::DeliverCargoToContract(json) => client.json(&json),
             },
             None => client.header(CONTENT_LENGTH, "0"),
@@ -201,13 +218,7 @@ impl SpaceTraders {
             Ok(msg) => Ok(msg),
         };
         match response {
-            Ok(response) => match response.text().await {
-                Err(err) => {
-                    error!("Error from response: {}", err);
-                    None
-                }
-                Ok(msg) => Some(msg),
-            },
+            Ok(response) => Some(response),
             Err(msg) => match msg {
                 reqwest_middleware::Error::Middleware(err) => {
                     error!("Error from reqwest middleware: {}", err);
@@ -247,6 +258,30 @@ impl SpaceTraders {
 
     // Systems
 
+    // downloads every system at once instead of paging through list_systems
+    // the body is large so it is deserialized while it is still being received
+    pub async fn list_all_systems(&self) -> Result<Vec<schemas::System>, SpacetradersError> {
+        let response = match self.send_reqwest(Method::Get, "/systems.json", None).await {
+            Some(response) => response,
+            None => return handle_response(None),
+        };
+        if !response.status().is_success() {
+            return handle_response(response.text().await.ok().as_deref());
+        }
+
+        let stream = response
+            .bytes_stream()
+            .map(|chunk| chunk.map_err(io::Error::other));
+        let reader = SyncIoBridge::new(StreamReader::new(stream));
+
+        match task::spawn_blocking(move || parse_systems_json(reader)).await {
+            Ok(systems) => systems,
+            Err(err) => {
+                error!("Failed to join systems.json parser: {}", err);
+                Err(SpacetradersError::Serde)
+            }
+        }
+    }
     async fn list_systems_page(
         &self,
         page: Option<u32>,
@@ -889,6 +924,36 @@ impl SpaceTraders {
             .as_deref(),
         )
     }
+    pub async fn install_module(
+        &self,
+        ship_symbol: &str,
+        data: InstallModule,
+    ) -> Result<fleet::InstallModules, SpacetradersError> {
+        handle_response(
+            self.make_reqwest(
+                Method::Post,
+                &format!("/my/ships/{}/modules/install", ship_symbol),
+                Some(Requests::InstallModule(data)),
+            )
+            .await
+            .as_deref(),
+        )
+    }
+    pub async fn remove_module(
+        &self,
+        ship_symbol: &str,
+        data: RemoveModule,
+    ) -> Result<fleet::RemoveModules, SpacetradersError> {
+        handle_response(
+            self.make_reqwest(
+                Method::Post,
+                &format!("/my/ships/{}/modules/remove", ship_symbol),
+                Some(Requests::RemoveModule(data)),
+            )
+            .await
+            .as_deref(),
+        )
+    }
 
     // Factions
     pub async fn list_factions(&self) -> Result<factions::Factions, SpacetradersError> {
@@ -957,6 +1022,22 @@ fn handle_response<T: for<'a> Deserialize<'a>>(
     }
 }
 
+// reads a systems.json body one system at a time
+fn parse_systems_json<R: io::Read>(reader: R) -> Result<Vec<schemas::System>, SpacetradersError> {
+    let jd = &mut serde_json::Deserializer::from_reader(io::BufReader::new(reader));
+    match serde_path_to_error::deserialize(jd) {
+        Ok(systems) => Ok(systems),
+        Err(err) => {
+            error!(
+                "SpaceTraders Error (Could not parse systems.json): {}, path: {}",
+                err.inner(),
+                err.path()
+            );
+            Err(SpacetradersError::Serde)
+        }
+    }
+}
+
 #[derive(Debug, Clone, Error, Eq, PartialEq)]
 pub enum SpacetradersError {
     CooldownConflictError,
@@ -1182,7 +1263,7 @@ fn parse_error(error_str: &str) -> Option<SpacetradersError> {
 }
 
 // Waypoint handlers //
-#[derive(Clone, Serialize, PartialEq, Eq, Hash, Debug)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct WaypointString {
     pub waypoint: String,
     pub system: String,
@@ -1201,12 +1282,20 @@ impl WaypointString {
         }
     }
 }
+impl Serialize for WaypointString {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.waypoint)
+    }
+}
 impl<'de> Deserialize<'de> for WaypointString {
     fn deserialize<D>(deserializer: D) -> Result<WaypointString, D::Error>
     where
         D: Deserializer<'de>,
     {
-        let s: &str = Deserialize::deserialize(deserializer)?;
+        let s: String = Deserialize::deserialize(deserializer)?;
         if s.contains('-') {
             let waypoint_split: Vec<&str> = s.split('-').collect();
             if waypoint_split.len() == 3 {
@@ -1217,7 +1306,7 @@ impl<'de> Deserialize<'de> for WaypointString {
                 })
             } else {
                 Err(D::Error::invalid_value(
-                    Unexpected::Str(s),
+                    Unexpected::Str(&s),
                     &"a String as Waypoint",
                 ))
             }
@@ -1230,7 +1319,7 @@ impl<'de> Deserialize<'de> for WaypointString {
         }
     }
 }
-#[derive(Clone, Serialize, PartialEq, Eq, Hash, Debug)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct SystemString {
     pub system: String,
     pub sector: String,
@@ -1242,12 +1331,20 @@ impl SystemString {
         }
     }
 }
+impl Serialize for SystemString {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.system)
+    }
+}
 impl<'de> Deserialize<'de> for SystemString {
     fn deserialize<D>(deserializer: D) -> Result<SystemString, D::Error>
     where
         D: Deserializer<'de>,
     {
-        let s: &str = Deserialize::deserialize(deserializer)?;
+        let s: String = Deserialize::deserialize(deserializer)?;
         if s.contains('-') {
             let split: Vec<&str> = s.split('-').collect();
             if split.len() == 2 {
@@ -1257,7 +1354,7 @@ impl<'de> Deserialize<'de> for SystemString {
                 })
             } else {
                 Err(D::Error::invalid_value(
-                    Unexpected::Str(s),
+                    Unexpected::Str(&s),
                     &"a String as System",
                 ))
             }
@@ -1269,17 +1366,25 @@ impl<'de> Deserialize<'de> for SystemString {
         }
     }
 }
-#[derive(Clone, Serialize, PartialEq, Eq, Hash, Debug)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct SectorString {
     pub sector: String,
 }
+impl Serialize for SectorString {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.sector)
+    }
+}
 #[allow(dead_code)]
 impl<'de> Deserialize<'de> for SectorString {
     fn deserialize<D>(deserializer: D) -> Result<SectorString, D::Error>
     where
         D: Deserializer<'de>,
     {
-        let s: &str = Deserialize::deserialize(deserializer)?;
+        let s: String = Deserialize::deserialize(deserializer)?;
         if s.contains('-') {
             let split: Vec<&str> = s.split('-').collect();
             if split.len() == 1 {
@@ -1288,7 +1393,7 @@ impl<'de> Deserialize<'de> for SectorString {
                 })
             } else {
                 Err(D::Error::invalid_value(
-                    Unexpected::Str(s),
+                    Unexpected::Str(&s),
                     &"a String as Sector",
                 ))
             }
@@ -1301,7 +1406,7 @@ impl<'de> Deserialize<'de> for SectorString {
 }
 
 pub mod spacetraders_datetime_format {
-    use chrono::{DateTime, Local, TimeZone};
+    use chrono::{DateTime, Local};
     // use log::error;
     use serde::{self, Deserialize, Deserializer, Serializer};
 
@@ -1320,8 +1425,8 @@ pub mod spacetraders_datetime_format {
         D: Deserializer<'de>,
     {
         let s = String::deserialize(deserializer)?;
-        match Local.datetime_from_str(&s, FORMAT) {
-            Ok(date) => Ok(date),
+        match DateTime::parse_from_str(&s, FORMAT) {
+            Ok(date) => Ok(date.with_timezone(&Local)),
             Err(_) => {
                 // error!("Failed deserializing chrono - defaulting to now!");
                 Ok(chrono::offset::Local::now())
@@ -1330,7 +1435,7 @@ pub mod spacetraders_datetime_format {
     }
 }
 pub mod spacetraders_date_format {
-    use chrono::{DateTime, Local, TimeZone};
+    use chrono::{DateTime, Local};
     // use log::error;
     use serde::{self, Deserialize, Deserializer, Serializer};
 
@@ -1349,10 +1454,12 @@ pub mod spacetraders_date_format {
         D: Deserializer<'de>,
     {
         let s = String::deserialize(deserializer)?;
-        let s = format!("{}{}", s, "T01:00:00Z");
+        // full timestamps are passed through so cached data survives a round trip
+        let parsed = DateTime::parse_from_str(&s, FORMAT)
+            .or_else(|_| DateTime::parse_from_str(&format!("{}{}", s, "T01:00:00Z"), FORMAT));
 
-        match Local.datetime_from_str(&s, FORMAT) {
-            Ok(date) => Ok(date),
+        match parsed {
+            Ok(date) => Ok(date.with_timezone(&Local)),
             Err(_) => {
                 // error!("Failed deserializing chrono - defaulting to now!");
                 Ok(Local::now())
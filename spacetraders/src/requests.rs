@@ -21,6 +21,8 @@ pub enum Requests {
     TransferCargo(TransferCargo),
     InstallMount(InstallMount),
     RemoveMount(RemoveMount),
+    InstallModule(InstallModule),
+    RemoveModule(RemoveModule),
     DeliverCargoToContract(DeliverCargoToContract),
 }
 
@@ -68,7 +70,7 @@ pub struct NavigateShip {
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct PatchShipNav {
-    pub ship_symbol: FlightMode,
+    pub flight_mode: FlightMode,
 }
 
 #[derive(Serialize, Debug)]
@@ -117,10 +119,22 @@ pub struct RemoveMount {
     pub symbol: String,
 }
 
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallModule {
+    pub symbol: String,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveModule {
+    pub symbol: String,
+}
+
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct DeliverCargoToContract {
-    pub ship_symbol: ShipType,
+    pub ship_symbol: String,
     pub trade_symbol: TradeSymbol,
     pub units: i64,
 }
@@ -309,3 +309,31 @@ pub struct RemoveMountsData {
     pub cargo: schemas::ShipCargo,
     pub transaction: schemas::ShipModificationTransaction,
 }
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug)]
+pub struct InstallModules {
+    pub data: InstallModulesData,
+}
+#[allow(dead_code)]
+#[derive(Deserialize, Debug)]
+pub struct InstallModulesData {
+    pub agent: schemas::Agent,
+    pub modules: Vec<schemas::ShipModule>,
+    pub cargo: schemas::ShipCargo,
+    pub transaction: schemas::ShipModificationTransaction,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug)]
+pub struct RemoveModules {
+    pub data: RemoveModulesData,
+}
+#[allow(dead_code)]
+#[derive(Deserialize, Debug)]
+pub struct RemoveModulesData {
+    pub agent: schemas::Agent,
+    pub modules: Vec<schemas::ShipModule>,
+    pub cargo: schemas::ShipCargo,
+    pub transaction: schemas::ShipModificationTransaction,
+}
@@ -29,7 +29,7 @@ pub struct Agent {
     pub starting_faction: Option<enums::FactionSymbols>,
 }
 
-#[derive(Deserialize, Clone, Default, Debug)]
+#[derive(Deserialize, Serialize, Clone, Default, Debug)]
 pub struct Chart {
     #[serde(alias = "waypointSymbol")]
     #[serde(default)]
@@ -68,12 +68,35 @@ pub struct Contract {
     pub accepted: bool,
     pub fulfilled: bool,
     #[serde(default)]
-    pub expiration: String,
+    #[serde(with = "spacetraders_date_format")]
+    pub expiration: DateTime<Local>,
     #[serde(default)]
     #[serde(alias = "deadlineToAccept")]
     #[serde(with = "spacetraders_date_format")]
     pub deadline_to_accept: DateTime<Local>,
 }
+impl Contract {
+    // units of the good still to be delivered, 0 if the contract doesn't want it
+    pub fn remaining_units(&self, symbol: &enums::TradeSymbol) -> i64 {
+        self.terms
+            .deliver
+            .iter()
+            .filter(|good| good.trade_symbol.as_ref() == Some(symbol))
+            .map(|good| good.remaining_units())
+            .sum()
+    }
+    // negative once the deadline has passed
+    pub fn time_to_deadline(&self) -> chrono::Duration {
+        self.terms.deadline - Local::now()
+    }
+    // every good has been delivered, so the contract can be fulfilled
+    pub fn is_complete(&self) -> bool {
+        self.terms
+            .deliver
+            .iter()
+            .all(|good| good.remaining_units() == 0)
+    }
+}
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct ContractDeliverGood {
@@ -87,6 +110,11 @@ pub struct ContractDeliverGood {
     #[serde(alias = "unitsFulfilled")]
     pub units_fulfilled: i64,
 }
+impl ContractDeliverGood {
+    pub fn remaining_units(&self) -> i64 {
+        (self.units_required - self.units_fulfilled).max(0)
+    }
+}
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct ContractPayment {
@@ -150,7 +178,7 @@ pub struct FactionTrait {
     // description: String,
 }
 
-#[derive(Deserialize, Default, Clone, Debug)]
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
 pub struct JumpGate {
     #[serde(alias = "jumpRange")]
     pub jump_range: f64,
@@ -161,7 +189,7 @@ pub struct JumpGate {
     #[serde(alias = "connectedSystems")]
     pub connected_systems: Vec<JumpGateConnectedSystems>,
 }
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct JumpGateConnectedSystems {
     pub symbol: SystemString,
     #[serde(alias = "sectorSymbol")]
@@ -177,7 +205,7 @@ pub struct JumpGateConnectedSystems {
     pub distance: i32,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Market {
     pub symbol: String,
     pub exports: Vec<MarketDetails>,
@@ -189,14 +217,14 @@ pub struct Market {
     #[serde(alias = "tradeGoods")]
     pub trade_goods: Vec<GetMarketTradeGood>,
 }
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct MarketDetails {
     pub symbol: enums::TradeSymbol,
     pub name: String,
     // pub description: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct GetMarketTradeGood {
     pub symbol: enums::TradeSymbol,
     #[serde(alias = "tradeVolume")]
@@ -208,7 +236,7 @@ pub struct GetMarketTradeGood {
     pub sell_price: f64,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct MarketTransaction {
     #[serde(alias = "waypointSymbol")]
     pub waypoint_symbol: String,
@@ -247,10 +275,25 @@ pub struct Ship {
     pub cargo: ShipCargo,
     pub fuel: ShipFuel,
 }
+impl Ship {
+    // ships don't report what they were bought as, so guess from the frame
+    pub fn ship_type(&self) -> Option<enums::ShipType> {
+        let refining = self
+            .modules
+            .iter()
+            .any(|module| module.symbol == enums::ShipModule::ModuleOreRefineryI);
+        match self.frame.symbol.ship_type()? {
+            enums::ShipType::ShipHeavyFreighter if refining => {
+                Some(enums::ShipType::ShipRefiningFreighter)
+            }
+            ship_type => Some(ship_type),
+        }
+    }
+}
 
 #[derive(Deserialize, Debug)]
 pub struct ScannedSystem {
-    pub symbol: String,
+    pub symbol: SystemString,
     #[serde(alias = "sectorSymbol")]
     pub sector_symbol: String,
     pub r#type: enums::SystemType,
@@ -262,8 +305,8 @@ pub struct ScannedSystem {
 #[derive(Deserialize, Debug)]
 pub struct ScannedWaypoint {
     #[serde(alias = "systemSymbol")]
-    pub system_symbol: String,
-    pub symbol: String,
+    pub system_symbol: SystemString,
+    pub symbol: WaypointString,
     pub r#type: enums::WaypointType,
     pub x: i32,
     pub y: i32,
@@ -272,8 +315,27 @@ pub struct ScannedWaypoint {
     #[serde(default)]
     pub chart: Chart,
     pub faction: SystemFaction,
+    #[serde(default, alias = "isUnderConstruction")]
+    pub is_under_construction: bool,
+}
+// a scan sees everything a waypoint lookup would
+impl From<ScannedWaypoint> for Waypoint {
+    fn from(scanned: ScannedWaypoint) -> Self {
+        Waypoint {
+            system_symbol: scanned.system_symbol,
+            symbol: scanned.symbol,
+            r#type: scanned.r#type,
+            x: scanned.x,
+            y: scanned.y,
+            orbitals: scanned.orbitals,
+            traits: scanned.traits,
+            chart: scanned.chart,
+            faction: scanned.faction,
+            is_under_construction: scanned.is_under_construction,
+        }
+    }
 }
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct ScannedWaypointOrbitals {
     pub symbol: String,
 }
@@ -282,7 +344,7 @@ pub struct ScannedWaypointFaction {
     #[serde(deserialize_with = "skip_faction_symbol")]
     pub symbol: Option<enums::FactionSymbols>,
 }
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct ScannedWaypointTrait {
     pub symbol: enums::WaypointTrait,
     pub name: String,
@@ -346,7 +408,7 @@ pub struct ShipCrew {
     pub wages: u32,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct ShipEngine {
     pub symbol: enums::ShipEngine,
     pub name: String,
@@ -357,7 +419,7 @@ pub struct ShipEngine {
     pub requirements: ShipRequirements,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct ShipFrame {
     pub symbol: enums::ShipFrame,
     pub name: String,
@@ -400,7 +462,7 @@ pub struct ShipModificationTransaction {
     pub timestamp: String,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct ShipModule {
     pub symbol: enums::ShipModule,
     // description: String,
@@ -412,7 +474,7 @@ pub struct ShipModule {
     pub requirements: ShipRequirements,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct ShipMount {
     pub symbol: enums::ShipMount,
     pub name: String,
@@ -456,7 +518,7 @@ pub struct ShipNavRouteWaypoint {
     pub y: i32,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct ShipReactor {
     pub symbol: enums::ShipReactor,
     pub name: String,
@@ -477,7 +539,7 @@ pub struct ShipRegistration {
     pub role: enums::ShipRole,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct ShipRequirements {
     #[serde(default)]
     pub power: i32,
@@ -487,7 +549,7 @@ pub struct ShipRequirements {
     pub slots: i32,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Shipyard {
     pub symbol: String,
     #[serde(alias = "shipTypes")]
@@ -497,12 +559,12 @@ pub struct Shipyard {
     #[serde(default)]
     pub ships: Vec<ShipyardShip>,
 }
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct ShipyardTypes {
     pub r#type: enums::ShipType,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct ShipyardShip {
     pub r#type: enums::ShipType,
     pub name: String,
@@ -516,7 +578,7 @@ pub struct ShipyardShip {
     pub mounts: Vec<ShipMount>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct ShipyardTransaction {
     #[serde(alias = "waypointSymbol")]
     pub waypoint_symbol: String,
@@ -574,7 +636,7 @@ pub struct TradeGoods {
     // descripton
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Waypoint {
     #[serde(alias = "systemSymbol")]
     pub system_symbol: SystemString,
@@ -588,6 +650,8 @@ pub struct Waypoint {
     pub chart: Chart,
     #[serde(default)]
     pub faction: SystemFaction,
+    #[serde(default, alias = "isUnderConstruction")]
+    pub is_under_construction: bool,
 }
 
 #[derive(Deserialize, Debug)]
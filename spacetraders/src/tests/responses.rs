@@ -2,8 +2,9 @@
 use crate::{
     enums::{self, FlightMode, ShipType, TradeSymbol},
     requests::{
-        ExtractResources, InstallMount, JettisonCargo, JumpShip, NavigateShip, PatchShipNav,
-        PurchaseCargo, PurchaseShip, RemoveMount, SellCargo, ShipRefine, TransferCargo, WarpShip,
+        ExtractResources, InstallModule, InstallMount, JettisonCargo, JumpShip, NavigateShip,
+        PatchShipNav, PurchaseCargo, PurchaseShip, RemoveModule, RemoveMount, SellCargo,
+        ShipRefine, TransferCargo, WarpShip,
     },
     responses::schemas::SurveyDeposit,
     tests::log,
@@ -11,7 +12,7 @@ use crate::{
 };
 
 use once_cell::sync::Lazy;
-use std::sync::Mutex;
+use tokio::sync::Mutex;
 
 const TIMES_TO_RUN: i32 = 10;
 const STRING: &str = "X1-OE";
@@ -25,7 +26,7 @@ async fn recieve_error() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .custom_endpoint(Method::Post, "/doesnotexist", None)
             .await;
     }
@@ -37,7 +38,7 @@ async fn get_new_registration() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .custom_endpoint(
                 Method::Post,
                 "/register",
@@ -58,7 +59,7 @@ async fn get_new_registration() {
 async fn get_status() {
     log();
     for _ in 0..TIMES_TO_RUN {
-        SPACETRADERS.lock().unwrap().get_status().await.unwrap();
+        SPACETRADERS.lock().await.get_status().await.unwrap();
     }
 }
 
@@ -66,7 +67,7 @@ async fn get_status() {
 async fn agent() {
     log();
     for _ in 0..TIMES_TO_RUN {
-        SPACETRADERS.lock().unwrap().agent().await.unwrap();
+        SPACETRADERS.lock().await.agent().await.unwrap();
     }
 }
 
@@ -74,21 +75,35 @@ async fn agent() {
 async fn list_systems() {
     log();
     for _ in 0..TIMES_TO_RUN {
-        SPACETRADERS
-            .lock()
-            .unwrap()
-            .list_systems(true)
-            .await
-            .unwrap();
+        SPACETRADERS.lock().await.list_systems(true).await.unwrap();
     }
 }
 #[tokio::test]
+async fn list_all_systems() {
+    log();
+    SPACETRADERS.lock().await.list_all_systems().await.unwrap();
+}
+#[test]
+fn parse_systems_json() {
+    log();
+    let systems = crate::parse_systems_json(
+        r#"[{"symbol":"X1-OE","sectorSymbol":"X1","type":"RED_STAR","x":-42,"y":7,
+            "waypoints":[{"symbol":"X1-OE-A1","type":"PLANET","x":1,"y":2,"orbitals":[]}],
+            "factions":[{"symbol":"COSMIC"}]}]"#
+            .as_bytes(),
+    )
+    .unwrap();
+    assert_eq!(systems.len(), 1);
+    assert_eq!(systems[0].symbol.system, STRING);
+    assert_eq!(systems[0].waypoints[0].symbol.system, STRING);
+}
+#[tokio::test]
 async fn get_systems() {
     log();
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .get_system(&SystemString {
                 system: STRING.to_string(),
                 sector: STRING.to_string(),
@@ -103,7 +118,7 @@ async fn list_waypoints() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .list_waypoints(
                 &SystemString {
                     system: STRING.to_string(),
@@ -121,7 +136,7 @@ async fn get_waypoint() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .get_waypoint(
                 &SystemString {
                     system: STRING.to_string(),
@@ -143,7 +158,7 @@ async fn get_market() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .get_market(
                 &SystemString {
                     system: STRING.to_string(),
@@ -165,7 +180,7 @@ async fn get_shipyard() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .get_shipyard(
                 &SystemString {
                     system: STRING.to_string(),
@@ -187,7 +202,7 @@ async fn get_jump_gate() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .jump_gate(&WaypointString {
                 waypoint: STRING.to_string(),
                 system: STRING.to_string(),
@@ -204,7 +219,7 @@ async fn list_contracts() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .list_contracts(true)
             .await
             .unwrap();
@@ -216,7 +231,7 @@ async fn get_contract() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .get_contract(STRING)
             .await
             .unwrap();
@@ -228,7 +243,7 @@ async fn accept_contracts() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .accept_contract(STRING)
             .await
             .unwrap();
@@ -240,11 +255,11 @@ async fn deliver_contract() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .deliver_contract(
                 STRING,
                 crate::requests::DeliverCargoToContract {
-                    ship_symbol: ShipType::ShipProbe,
+                    ship_symbol: STRING.to_string(),
                     trade_symbol: TradeSymbol::PreciousStones,
                     units: 1000,
                 },
@@ -259,30 +274,126 @@ async fn fulfill_contract() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .fulfill_contract(STRING)
             .await
             .unwrap();
     }
 }
 
+#[tokio::test]
+async fn contract_round_trip() {
+    log();
+    let spacetraders = SPACETRADERS.lock().await;
+    let contract = spacetraders
+        .accept_contract(STRING)
+        .await
+        .unwrap()
+        .data
+        .contract;
+    for good in contract.terms.deliver.iter() {
+        let Some(symbol) = &good.trade_symbol else {
+            continue;
+        };
+        let units = good.remaining_units();
+        let delivered = spacetraders
+            .deliver_contract(
+                &contract.id,
+                crate::requests::DeliverCargoToContract {
+                    ship_symbol: STRING.to_string(),
+                    trade_symbol: symbol.clone(),
+                    units,
+                },
+            )
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(
+            delivered.contract.remaining_units(symbol),
+            good.units_required - good.units_fulfilled - units
+        );
+    }
+    spacetraders.fulfill_contract(&contract.id).await.unwrap();
+}
+#[test]
+fn contract_helpers() {
+    log();
+    let contract: crate::responses::schemas::Contract = serde_json::from_str(
+        r#"{"id":"contract","factionSymbol":"COSMIC","type":"PROCUREMENT",
+            "terms":{"deadline":"2099-01-01T00:00:00.000Z",
+                "payment":{"onAccepted":100,"onFulfilled":1000},
+                "deliver":[
+                    {"tradeSymbol":"IRON_ORE","destinationSymbol":"X1-OE-A1",
+                     "unitsRequired":50,"unitsFulfilled":20},
+                    {"tradeSymbol":"COPPER_ORE","destinationSymbol":"X1-OE-A1",
+                     "unitsRequired":10,"unitsFulfilled":10}]},
+            "accepted":true,"fulfilled":false,
+            "expiration":"2099-01-01T00:00:00.000Z",
+            "deadlineToAccept":"2099-01-01T00:00:00.000Z"}"#,
+    )
+    .unwrap();
+    assert_eq!(contract.remaining_units(&TradeSymbol::IronOre), 30);
+    assert_eq!(contract.remaining_units(&TradeSymbol::CopperOre), 0);
+    assert_eq!(contract.remaining_units(&TradeSymbol::Gold), 0);
+    assert!(!contract.is_complete());
+    assert!(contract.time_to_deadline() > chrono::Duration::zero());
+    assert!(contract.expiration > chrono::Local::now());
+}
+#[test]
+fn trade_good_conversions() {
+    log();
+    let laser = enums::ShipMount::MountMiningLaserIi;
+    assert_eq!(laser.trade_symbol(), Some(TradeSymbol::MountMiningLaserIi));
+    assert_eq!(TradeSymbol::MountMiningLaserIi.mount(), Some(laser.clone()));
+    assert_eq!(
+        TradeSymbol::ModuleCargoHoldI.module(),
+        Some(enums::ShipModule::ModuleCargoHoldI)
+    );
+    assert_eq!(TradeSymbol::IronOre.mount(), None);
+    assert_eq!(enums::ShipMount::Default.trade_symbol(), None);
+    assert_eq!(
+        ShipType::ShipOreHound
+            .frame()
+            .and_then(|frame| frame.ship_type()),
+        Some(ShipType::ShipOreHound)
+    );
+
+    assert_eq!(TradeSymbol::IronOre.category(), enums::GoodCategory::RawOre);
+    assert_eq!(TradeSymbol::IronOre.refines_into(), Some(TradeSymbol::Iron));
+    assert_eq!(TradeSymbol::Iron.category(), enums::GoodCategory::Refined);
+    assert_eq!(
+        TradeSymbol::Hydrocarbon.refines_into(),
+        Some(TradeSymbol::Fuel)
+    );
+    assert_eq!(TradeSymbol::Fuel.category(), enums::GoodCategory::Fuel);
+    assert_eq!(
+        TradeSymbol::Antimatter.category(),
+        enums::GoodCategory::Antimatter
+    );
+    assert_eq!(
+        TradeSymbol::ReactorFusionI.category(),
+        enums::GoodCategory::Equipment
+    );
+    assert_eq!(TradeSymbol::Food.category(), enums::GoodCategory::Other);
+    assert!(!TradeSymbol::QuartzSand.is_refinable());
+    assert_eq!(
+        TradeSymbol::LiquidHydrogen.extractor(),
+        Some(enums::Extractor::GasSiphon)
+    );
+    assert_eq!(TradeSymbol::QuartzSand.extractor(), laser.extractor());
+}
 #[tokio::test]
 async fn list_factions() {
     log();
     for _ in 0..TIMES_TO_RUN {
-        SPACETRADERS.lock().unwrap().list_factions().await.unwrap();
+        SPACETRADERS.lock().await.list_factions().await.unwrap();
     }
 }
 #[tokio::test]
 async fn get_faction() {
     log();
     for _ in 0..TIMES_TO_RUN {
-        SPACETRADERS
-            .lock()
-            .unwrap()
-            .get_faction(STRING)
-            .await
-            .unwrap();
+        SPACETRADERS.lock().await.get_faction(STRING).await.unwrap();
     }
 }
 
@@ -290,7 +401,7 @@ async fn get_faction() {
 async fn list_ships() {
     log();
     for _ in 0..TIMES_TO_RUN {
-        SPACETRADERS.lock().unwrap().list_ships().await.unwrap();
+        SPACETRADERS.lock().await.list_ships().await.unwrap();
     }
 }
 #[tokio::test]
@@ -299,7 +410,7 @@ async fn purchase_ship() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .purchase_ship(PurchaseShip {
                 waypoint_symbol: STRING.to_string(),
                 ship_type: ShipType::ShipMiningDrone,
@@ -312,7 +423,7 @@ async fn purchase_ship() {
 async fn get_ship() {
     log();
     for _ in 0..TIMES_TO_RUN {
-        SPACETRADERS.lock().unwrap().get_ship(STRING).await.unwrap();
+        SPACETRADERS.lock().await.get_ship(STRING).await.unwrap();
     }
 }
 #[tokio::test]
@@ -321,7 +432,7 @@ async fn get_ship_cargo() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .get_ship_cargo(STRING)
             .await
             .unwrap();
@@ -331,12 +442,7 @@ async fn get_ship_cargo() {
 async fn orbit_ship() {
     log();
     for _ in 0..TIMES_TO_RUN {
-        SPACETRADERS
-            .lock()
-            .unwrap()
-            .orbit_ship(STRING)
-            .await
-            .unwrap();
+        SPACETRADERS.lock().await.orbit_ship(STRING).await.unwrap();
     }
 }
 #[tokio::test]
@@ -345,7 +451,7 @@ async fn ship_refine() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .ship_refine(
                 "fsd",
                 ShipRefine {
@@ -362,7 +468,7 @@ async fn create_chart() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .create_chart(STRING)
             .await
             .unwrap();
@@ -374,7 +480,7 @@ async fn get_ship_cooldown() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .get_ship_cooldown(STRING)
             .await
             .unwrap();
@@ -384,12 +490,7 @@ async fn get_ship_cooldown() {
 async fn dock_ship() {
     log();
     for _ in 0..TIMES_TO_RUN {
-        SPACETRADERS
-            .lock()
-            .unwrap()
-            .dock_ship(STRING)
-            .await
-            .unwrap();
+        SPACETRADERS.lock().await.dock_ship(STRING).await.unwrap();
     }
 }
 #[tokio::test]
@@ -398,7 +499,7 @@ async fn create_survey() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .create_survey(STRING)
             .await
             .unwrap();
@@ -410,7 +511,7 @@ async fn extract_resources() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .extract_resources(
                 STRING,
                 Some(ExtractResources {
@@ -436,7 +537,7 @@ async fn jettison_cargo() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .jettison_cargo(
                 STRING,
                 JettisonCargo {
@@ -454,7 +555,7 @@ async fn jump_ship() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .jump_ship(
                 STRING,
                 JumpShip {
@@ -471,7 +572,7 @@ async fn navigate_ship() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .navigate_ship(
                 STRING,
                 NavigateShip {
@@ -488,11 +589,11 @@ async fn patch_ship_nav() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .patch_ship_nav(
                 STRING,
                 PatchShipNav {
-                    ship_symbol: FlightMode::Cruise,
+                    flight_mode: FlightMode::Cruise,
                 },
             )
             .await
@@ -505,7 +606,7 @@ async fn get_ship_nav() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .get_ship_nav(STRING)
             .await
             .unwrap();
@@ -517,7 +618,7 @@ async fn warp_ship() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .warp_ship(
                 STRING,
                 WarpShip {
@@ -534,7 +635,7 @@ async fn sell_cargo() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .sell_cargo(
                 STRING,
                 SellCargo {
@@ -552,7 +653,7 @@ async fn scan_systems() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .scan_systems(STRING)
             .await
             .unwrap();
@@ -564,7 +665,7 @@ async fn scan_waypoints() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .scan_waypoints(STRING)
             .await
             .unwrap();
@@ -574,12 +675,7 @@ async fn scan_waypoints() {
 async fn scan_ships() {
     log();
     for _ in 0..TIMES_TO_RUN {
-        SPACETRADERS
-            .lock()
-            .unwrap()
-            .scan_ships(STRING)
-            .await
-            .unwrap();
+        SPACETRADERS.lock().await.scan_ships(STRING).await.unwrap();
     }
 }
 #[tokio::test]
@@ -588,7 +684,7 @@ async fn purchase_cargo() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .purchase_cargo(
                 STRING,
                 PurchaseCargo {
@@ -606,7 +702,7 @@ async fn transfer_cargo() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .transfer_cargo(
                 STRING,
                 TransferCargo {
@@ -625,7 +721,7 @@ async fn negotiate_contract() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .negotiate_contract(STRING)
             .await
             .unwrap();
@@ -635,12 +731,7 @@ async fn negotiate_contract() {
 async fn get_mounts() {
     log();
     for _ in 0..TIMES_TO_RUN {
-        SPACETRADERS
-            .lock()
-            .unwrap()
-            .get_mounts(STRING)
-            .await
-            .unwrap();
+        SPACETRADERS.lock().await.get_mounts(STRING).await.unwrap();
     }
 }
 #[tokio::test]
@@ -649,7 +740,7 @@ async fn install_mount() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .install_mount(
                 STRING,
                 InstallMount {
@@ -666,7 +757,7 @@ async fn remove_mount() {
     for _ in 0..TIMES_TO_RUN {
         SPACETRADERS
             .lock()
-            .unwrap()
+            .await
             .remove_mount(
                 STRING,
                 RemoveMount {
@@ -677,3 +768,37 @@ async fn remove_mount() {
             .unwrap();
     }
 }
+#[tokio::test]
+async fn install_module() {
+    log();
+    for _ in 0..TIMES_TO_RUN {
+        SPACETRADERS
+            .lock()
+            .await
+            .install_module(
+                STRING,
+                InstallModule {
+                    symbol: STRING.to_string(),
+                },
+            )
+            .await
+            .unwrap();
+    }
+}
+#[tokio::test]
+async fn remove_module() {
+    log();
+    for _ in 0..TIMES_TO_RUN {
+        SPACETRADERS
+            .lock()
+            .await
+            .remove_module(
+                STRING,
+                RemoveModule {
+                    symbol: STRING.to_string(),
+                },
+            )
+            .await
+            .unwrap();
+    }
+}